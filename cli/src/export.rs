@@ -0,0 +1,58 @@
+use pps_engine::{
+    apis::export::{ExportFormat, ExportRequest, ExportUpdate},
+    operation::Outcome,
+};
+use std::path::PathBuf;
+
+#[derive(clap::Clap, Debug)]
+pub struct ExportArgs {
+    /// Path to package: compiled for kattis format, source for polygon format
+    #[clap(long = "pkg", short = 'P')]
+    pub pkg_path: PathBuf,
+    /// Output path. If it ends with .zip, archive is created
+    #[clap(long = "out", short = 'O')]
+    pub out_path: PathBuf,
+    /// Rewrite dir
+    #[clap(long, short = 'F')]
+    pub force: bool,
+    /// Target format
+    #[clap(long, possible_values = &["kattis", "polygon"], default_value = "kattis")]
+    pub format: String,
+}
+
+#[tracing::instrument(skip(args))]
+pub(crate) async fn exec(args: ExportArgs) -> anyhow::Result<()> {
+    let format = match args.format.as_str() {
+        "kattis" => ExportFormat::Kattis,
+        "polygon" => ExportFormat::Polygon,
+        other => anyhow::bail!("unknown format: {}", other),
+    };
+    let req = ExportRequest {
+        package_path: args.pkg_path.clone(),
+        out_path: args.out_path.clone(),
+        force: args.force,
+        format,
+    };
+    let mut op = pps_engine::apis::export::exec(req);
+    while let Some(upd) = op.next_update().await {
+        match upd {
+            ExportUpdate::Diagnostic(diagnostic) => eprintln!("{}", diagnostic),
+            ExportUpdate::ExportTests { count } => println!("Exporting {} tests", count),
+            ExportUpdate::ExportChecker => println!("Exporting checker"),
+            ExportUpdate::ExportSolution(name) => println!("Exporting solution '{}'", name),
+            ExportUpdate::CreateArchive => println!("Creating archive"),
+        }
+    }
+    match op.outcome() {
+        Outcome::Finish => {
+            println!("Problem exported successfully");
+        }
+        Outcome::Error(err) => {
+            anyhow::bail!("export failed: {:#}", err);
+        }
+        Outcome::Cancelled => {
+            println!("Operation was cancelled");
+        }
+    }
+    Ok(())
+}
@@ -1,9 +1,9 @@
 use anyhow::Context as _;
 use pps_engine::{
-    apis::import::{ImportRequest, ImportUpdate, PropertyName},
+    apis::import::{ImportRequest, ImportUpdate, PropertyName, SyncAction},
     operation::Outcome,
 };
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 #[derive(clap::Clap, Debug)]
 pub struct ImportArgs {
@@ -16,18 +16,33 @@ pub struct ImportArgs {
     /// Rewrite dir
     #[clap(long, short = 'F')]
     pub force: bool,
+    /// Import tests and answers stored in package instead of running generators
+    #[clap(long)]
+    pub static_tests: bool,
+    /// Fail unless imported package has this revision
+    #[clap(long)]
+    pub revision: Option<u32>,
     /// Imported contest name
     /// This option can only be used when importing contest
     #[clap(long, short = 'N')]
     pub contest_name: Option<String>,
+    /// Update previously imported package, preserving local edits
+    #[clap(long)]
+    pub sync: bool,
 }
 
-async fn import_one_problem(src: &Path, dest: &Path, force: bool) -> anyhow::Result<()> {
+async fn import_package(args: &ImportArgs) -> anyhow::Result<()> {
     let import_req = ImportRequest {
-        src_path: src.to_path_buf(),
-        out_path: dest.to_path_buf(),
-        force,
+        src_path: args.in_path.clone(),
+        out_path: args.out_path.clone(),
+        force: args.force,
+        static_tests: args.static_tests,
+        revision: args.revision,
+        contest_name: args.contest_name.clone(),
+        sync: args.sync,
     };
+    let mut is_contest = false;
+    let mut conflicts = 0;
     let mut op = pps_engine::apis::import::exec(import_req);
     while let Some(upd) = op.next_update().await {
         match upd {
@@ -47,20 +62,50 @@ async fn import_one_problem(src: &Path, dest: &Path, force: bool) -> anyhow::Res
                     println!("Test output file path pattern: {}", property_value)
                 }
                 PropertyName::ProblemTitle => println!("Problem title: {}", property_value),
+                PropertyName::Revision => println!("Revision: {}", property_value),
+                PropertyName::ContestTitle => println!("Contest title: {}", property_value),
             },
-            ImportUpdate::Warning(warning) => eprintln!("warning: {}", warning),
+            ImportUpdate::ExtractArchive => println!("Extracting package archive"),
+            ImportUpdate::ImportProblem(code) => {
+                is_contest = true;
+                println!("Importing problem {}", code);
+            }
+            ImportUpdate::Diagnostic(diagnostic) => eprintln!("{}", diagnostic),
             ImportUpdate::ImportChecker => println!("Importing checker"),
+            ImportUpdate::BuiltinChecker(name) => println!("Using builtin checker '{}'", name),
             ImportUpdate::ImportTests => println!("Importing tests"),
             ImportUpdate::ImportTestsDone { count } => println!("{} tests imported", count),
             ImportUpdate::ImportSolutions => println!("Importing solutions"),
             ImportUpdate::ImportSolution(solution) => println!("Importing solution '{}'", solution),
             ImportUpdate::ImportValuerConfig => println!("Importing valuer config"),
             ImportUpdate::DefaultValuerConfig => println!("Defaulting valuer config"),
+            ImportUpdate::GenerateValuerConfig => {
+                println!("Generating valuer config from test groups")
+            }
+            ImportUpdate::SyncFile { path, action } => match action {
+                SyncAction::Added => println!("Added {}", path),
+                SyncAction::Updated => println!("Updated {}", path),
+                SyncAction::Removed => println!("Removed {}", path),
+                SyncAction::Conflict => {
+                    conflicts += 1;
+                    eprintln!("conflict: {} was changed both upstream and locally", path);
+                }
+            },
         }
     }
     match op.outcome() {
         Outcome::Finish => {
-            println!("Problem imported successfully");
+            if conflicts != 0 {
+                eprintln!(
+                    "{} conflicts: local versions are kept, upstream versions are saved with .upstream suffix",
+                    conflicts
+                );
+            }
+            if is_contest {
+                println!("Contest imported successfully");
+            } else {
+                println!("Problem imported successfully");
+            }
         }
         Outcome::Error(err) => {
             anyhow::bail!("import failed: {:#}", err);
@@ -74,19 +119,19 @@ async fn import_one_problem(src: &Path, dest: &Path, force: bool) -> anyhow::Res
 
 #[tracing::instrument(skip(args))]
 pub(crate) async fn exec(args: ImportArgs) -> anyhow::Result<()> {
-    if args.force {
+    if args.sync {
+        if args.force {
+            anyhow::bail!("--sync and --force can not be used together");
+        }
+        std::fs::create_dir_all(&args.out_path).context("create out dir")?;
+    } else if args.force {
         std::fs::remove_dir_all(&args.out_path).ok();
         std::fs::create_dir(&args.out_path).context("create out dir")?;
     } else {
         crate::check_dir(&PathBuf::from(&args.out_path), false /* TODO */)?;
     }
 
-    let src = &args.in_path;
-    let dest = &args.out_path;
-
-    import_one_problem(src, dest, args.force).await?;
-
-    // TODO support importing contests
+    import_package(&args).await?;
 
     Ok(())
 }
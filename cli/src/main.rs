@@ -1,4 +1,5 @@
 mod compile;
+mod export;
 mod import;
 mod progress_notifier;
 
@@ -11,6 +12,7 @@ use std::path::Path;
 pub enum Args {
     Compile(compile::CompileArgs),
     Import(import::ImportArgs),
+    Export(export::ExportArgs),
 }
 
 fn check_dir(path: &Path, allow_nonempty: bool) -> anyhow::Result<()> {
@@ -42,5 +44,6 @@ async fn process_args(args: Args) -> anyhow::Result<()> {
     match args {
         Args::Compile(compile_args) => compile::exec(compile_args).await,
         Args::Import(import_args) => import::exec(import_args).await,
+        Args::Export(export_args) => export::exec(export_args).await,
     }
 }
@@ -1,3 +1,4 @@
 //! This module contains actual APIs, provided by the pps-engine
 pub mod compile;
+pub mod export;
 pub mod import;
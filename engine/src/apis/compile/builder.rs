@@ -266,8 +266,24 @@ impl<'a> ProblemBuilder<'a> {
                 correct: None,
                 limits: merge_limits(&[self.cfg.limits, test_spec.limits]),
                 group: test_spec.group.clone(),
+                tags: test_spec.tags.clone(),
             };
-            if let Some(cmd) = gen_answers {
+            if let Some(answer) = &test_spec.answer {
+                let src_path = self.problem_dir.join("tests").join(answer);
+                let correct_file_path = format!("{}/{}-out.txt", &tests_path, tid);
+                if let Err(e) = tokio::fs::copy(&src_path, &correct_file_path).await {
+                    anyhow::bail!(
+                        "Couldn't copy correct answer from {} to {}: {}",
+                        src_path.display(),
+                        correct_file_path,
+                        e,
+                    );
+                }
+                test_info.correct.replace(FileRef {
+                    path: format!("tests/{}-out.txt", tid),
+                    root: FileRefRoot::Problem,
+                });
+            } else if let Some(cmd) = gen_answers {
                 let test_data = tokio::fs::File::open(&out_file_path).await?;
 
                 let correct_file_path = format!("{}/{}-out.txt", &tests_path, tid);
@@ -421,6 +437,8 @@ impl<'a> ProblemBuilder<'a> {
                 crate::manifest::Check::Custom(cs) => cs.pass_correct,
                 crate::manifest::Check::Builtin(_) => true,
             };
+            // answers provided by problem source do not have to be generated
+            let gen_answers = gen_answers && self.cfg.tests.iter().any(|t| t.answer.is_none());
             let gen_answers = if gen_answers {
                 let primary_solution_name = self.cfg.primary_solution.as_ref().context(
                     "primary-solution must be specified in order to generate tests correct answers",
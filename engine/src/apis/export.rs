@@ -0,0 +1,232 @@
+//! This module implements exporting packages into formats of other systems
+mod kattis;
+mod polygon;
+
+use crate::{
+    apis::import::Diagnostic,
+    operation::{Operation, ProgressWriter},
+};
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ExportFormat {
+    /// Kattis problem package format, also supported by DOMjudge.
+    /// Requires compiled package.
+    Kattis,
+    /// Polygon package with `problem.xml`.
+    /// Requires source package.
+    Polygon,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportRequest {
+    /// Path to exported package.
+    /// Kattis export requires compiled package, Polygon export requires source package.
+    pub package_path: PathBuf,
+    /// Where to put exported problem.
+    /// If it has `.zip` extension, zip archive is created.
+    pub out_path: PathBuf,
+    /// Ignore existing files in out_path
+    pub force: bool,
+    pub format: ExportFormat,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum ExportUpdate {
+    /// Problem feature can not be exported exactly.
+    /// May appear multiple times.
+    Diagnostic(Diagnostic),
+    /// Tests are being exported. `count` tests will be processed.
+    ExportTests { count: usize },
+    /// Checker is being adapted to target format
+    ExportChecker,
+    /// Solution with given name is being exported
+    ExportSolution(String),
+    /// Exported problem is being packed into zip archive
+    CreateArchive,
+}
+
+/// Returns tags of tests which are samples.
+/// Samples are groups named `sample` or `samples`, or groups
+/// which give no points and have full feedback in valuer config.
+fn sample_tags(config: Option<&svaluer::Config>) -> Vec<String> {
+    let mut tags = vec!["sample".to_string(), "samples".to_string()];
+    for group in config.map(|c| c.groups.as_slice()).unwrap_or_default() {
+        let gives_points = group.score != 0 || group.test_score.is_some();
+        if !gives_points && matches!(group.feedback, svaluer::cfg::FeedbackKind::Full) {
+            tags.push(group.tests_tag().to_string());
+        }
+    }
+    tags
+}
+
+/// Checks if test with given `group` and additional `tags` is a sample
+fn is_sample(sample_tags: &[String], group: &str, tags: &[String]) -> bool {
+    std::iter::once(group)
+        .chain(tags.iter().map(String::as_str))
+        .any(|tag| sample_tags.iter().any(|sample| sample == tag))
+}
+
+fn is_archive(path: &Path) -> bool {
+    path.extension() == Some(std::ffi::OsStr::new("zip"))
+}
+
+/// Packs contents of directory `src` into zip archive `dest`
+async fn create_archive(src: &Path, dest: &Path) -> anyhow::Result<()> {
+    let src = src.to_path_buf();
+    let dest = dest.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::create(&dest)
+            .with_context(|| format!("failed to create {}", dest.display()))?;
+        let mut archive = zip::ZipWriter::new(file);
+        let mut stack = vec![src.clone()];
+        while let Some(dir) = stack.pop() {
+            let mut entries = std::fs::read_dir(&dir)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.sort();
+            for path in entries {
+                let name = path
+                    .strip_prefix(&src)
+                    .expect("path is inside src")
+                    .to_string_lossy()
+                    .to_string();
+                if path.is_dir() {
+                    archive.add_directory(name, Default::default())?;
+                    stack.push(path);
+                    continue;
+                }
+                let mode = {
+                    use std::os::unix::fs::PermissionsExt as _;
+                    std::fs::metadata(&path)?.permissions().mode()
+                };
+                let options = zip::write::FileOptions::default().unix_permissions(mode);
+                archive.start_file(name, options)?;
+                let mut input = std::fs::File::open(&path)?;
+                std::io::copy(&mut input, &mut archive)?;
+            }
+        }
+        archive.finish()?;
+        Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+async fn do_exec(req: ExportRequest, pw: &mut ProgressWriter<ExportUpdate>) -> anyhow::Result<()> {
+    // when exporting into archive, files are first written into temporary dir
+    let mut temp_dir = None;
+    let out_dir = if is_archive(&req.out_path) {
+        if req.out_path.exists() && !req.force {
+            anyhow::bail!("error: {} already exists", req.out_path.display());
+        }
+        let dir = tempfile::Builder::new()
+            .prefix("pps-export-")
+            .tempdir()
+            .context("failed to create temporary dir")?;
+        let path = dir.path().to_path_buf();
+        temp_dir.replace(dir);
+        path
+    } else {
+        if req.force {
+            tokio::fs::remove_dir_all(&req.out_path).await.ok();
+            tokio::fs::create_dir_all(&req.out_path).await?;
+        } else {
+            crate::check_dir(&req.out_path, false).await?;
+        }
+        req.out_path.clone()
+    };
+    match req.format {
+        ExportFormat::Kattis => kattis::export(&req.package_path, &out_dir, pw).await?,
+        ExportFormat::Polygon => polygon::export(&req.package_path, &out_dir, pw).await?,
+    }
+    if temp_dir.is_some() {
+        pw.send(ExportUpdate::CreateArchive).await;
+        create_archive(&out_dir, &req.out_path).await?;
+    }
+    drop(temp_dir);
+    Ok(())
+}
+
+/// Executes ExportRequest
+pub fn exec(req: ExportRequest) -> Operation<ExportUpdate> {
+    let (op, mut pw) = crate::operation::start();
+    tokio::task::spawn(async move {
+        let res = do_exec(req, &mut pw).await;
+        pw.finish(res).await;
+    });
+
+    op
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::write;
+
+    #[test]
+    fn sample_tags_heuristic() {
+        let config: svaluer::Config = serde_yaml::from_str(
+            "
+groups:
+  - name: examples
+    feedback: full
+    score: 0
+  - name: per-test
+    feedback: full
+    score: 0
+    test_score: 2
+  - name: main
+    feedback: full
+    score: 100
+  - name: hidden
+    feedback: hidden
+    score: 0
+",
+        )
+        .unwrap();
+        assert_eq!(
+            sample_tags(Some(&config)),
+            ["sample", "samples", "examples"]
+        );
+        assert_eq!(sample_tags(None), ["sample", "samples"]);
+    }
+
+    #[tokio::test]
+    async fn archive_creation() {
+        let src = tempfile::tempdir().unwrap();
+        write(src.path(), "data/sample/01.in", "1 2");
+        write(src.path(), "problem.yaml", "name: test");
+        let dest = tempfile::tempdir().unwrap();
+        let archive_path = dest.path().join("problem.zip");
+        assert!(is_archive(&archive_path));
+        create_archive(src.path(), &archive_path).await.unwrap();
+
+        let mut archive =
+            zip::ZipArchive::new(std::fs::File::open(&archive_path).unwrap()).unwrap();
+        let mut names: Vec<_> = archive.file_names().map(ToString::to_string).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["data/", "data/sample/", "data/sample/01.in", "problem.yaml"]
+        );
+        let mut data = String::new();
+        std::io::Read::read_to_string(
+            &mut archive.by_name("data/sample/01.in").unwrap(),
+            &mut data,
+        )
+        .unwrap();
+        assert_eq!(data, "1 2");
+    }
+
+    #[test]
+    fn sample_by_extra_tag() {
+        let sample_tags = sample_tags(None);
+        let tags = ["sample".to_string()];
+        assert!(is_sample(&sample_tags, "samples", &[]));
+        assert!(is_sample(&sample_tags, "main", &tags));
+        assert!(!is_sample(&sample_tags, "main", &[]));
+    }
+}
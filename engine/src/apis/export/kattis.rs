@@ -0,0 +1,248 @@
+//! Exports compiled package into Kattis problem package format.
+//! Checker is kept as is and wrapped into output validator,
+//! which translates Kattis validator interface into pps checker protocol.
+use super::ExportUpdate;
+use crate::{
+    apis::import::{Diagnostic, DiagnosticCode},
+    operation::ProgressWriter,
+};
+use anyhow::Context as _;
+use std::path::{Path, PathBuf};
+
+/// Name of output validator directory
+const VALIDATOR_NAME: &str = "pps_checker";
+
+/// Output validator entry point.
+/// Kattis runs it as `run input_file answer_file feedback_dir [flags] < team_output`.
+const RUN_SCRIPT_TPL: &str = r#"#!/bin/sh
+# Adapts pps checker to Kattis output validator interface
+FEEDBACK_DIR="$(cd "$3" && pwd)" || exit 1
+exec 3<"$1" 4<"$2" 5>"$FEEDBACK_DIR/checker_out.txt" 6>"$FEEDBACK_DIR/judgemessage.txt" || exit 1
+cd "$(dirname "$0")" || exit 1
+JJS_SOL=0 JJS_TEST=3 JJS_CORR=4 JJS_CHECKER_OUT=5 JJS_CHECKER_COMMENT=6 ./checker{args}
+case "$(cat "$FEEDBACK_DIR/checker_out.txt")" in
+    outcome=Ok*) exit 42 ;;
+    outcome=WrongAnswer*|outcome=PresentationError*) exit 43 ;;
+    *) exit 1 ;;
+esac
+"#;
+
+/// Checker binary is prebuilt, so nothing has to be compiled
+const BUILD_SCRIPT: &str = "#!/bin/sh\n";
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r#"'\''"#))
+}
+
+fn set_executable(path: &Path) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt as _;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("failed to make {} executable", path.display()))
+}
+
+struct Exporter<'a> {
+    /// Compiled package root
+    src: &'a Path,
+    dest: &'a Path,
+    problem: pom::Problem,
+    pw: &'a mut ProgressWriter<ExportUpdate>,
+}
+
+impl<'a> Exporter<'a> {
+    async fn report(&mut self, diagnostic: Diagnostic) {
+        self.pw.send(ExportUpdate::Diagnostic(diagnostic)).await;
+    }
+
+    fn resolve(&self, file_ref: &pom::FileRef) -> anyhow::Result<PathBuf> {
+        match file_ref.root {
+            pom::FileRefRoot::Problem => Ok(self.src.join("assets").join(&file_ref.path)),
+            pom::FileRefRoot::Root => anyhow::bail!(
+                "file {} is outside of problem package and can not be exported",
+                file_ref.path
+            ),
+        }
+    }
+
+    /// Returns tags of tests which are samples
+    fn sample_groups(&self) -> Vec<String> {
+        let cfg_path = self.src.join("assets/valuer-cfg/cfg.yaml");
+        let config = std::fs::read_to_string(cfg_path)
+            .ok()
+            .and_then(|data| serde_yaml::from_str::<svaluer::Config>(&data).ok());
+        super::sample_tags(config.as_ref())
+    }
+
+    async fn export_tests(&mut self) -> anyhow::Result<()> {
+        self.pw
+            .send(ExportUpdate::ExportTests {
+                count: self.problem.tests.len(),
+            })
+            .await;
+        let sample_groups = self.sample_groups();
+        let mut secret_groups = Vec::new();
+        for dir in &["data/sample", "data/secret"] {
+            tokio::fs::create_dir_all(self.dest.join(dir)).await?;
+        }
+        for (idx, test) in self.problem.tests.clone().iter().enumerate() {
+            let test_id = idx + 1;
+            let dir = if super::is_sample(&sample_groups, &test.group, &test.tags) {
+                "data/sample"
+            } else {
+                if !secret_groups.contains(&test.group) {
+                    secret_groups.push(test.group.clone());
+                }
+                "data/secret"
+            };
+            let input = self.resolve(&test.path)?;
+            let dest = self.dest.join(dir);
+            tokio::fs::copy(&input, dest.join(format!("{:0>2}.in", test_id)))
+                .await
+                .with_context(|| format!("failed to copy {}", input.display()))?;
+            let answer_path = dest.join(format!("{:0>2}.ans", test_id));
+            match &test.correct {
+                Some(correct) => {
+                    let answer = self.resolve(correct)?;
+                    tokio::fs::copy(&answer, &answer_path)
+                        .await
+                        .with_context(|| format!("failed to copy {}", answer.display()))?;
+                }
+                None => {
+                    // Kattis requires answer file for each test
+                    tokio::fs::write(&answer_path, "").await?;
+                }
+            }
+        }
+        if secret_groups.len() > 1 {
+            let message = format!(
+                "tests of groups {} are exported as single pass-fail testset, scores are not exported",
+                secret_groups.join(", ")
+            );
+            self.report(Diagnostic::warning(
+                DiagnosticCode::UnsupportedFeature,
+                message,
+            ))
+            .await;
+        }
+        Ok(())
+    }
+
+    async fn export_checker(&mut self) -> anyhow::Result<()> {
+        self.pw.send(ExportUpdate::ExportChecker).await;
+        let validator_dir = self.dest.join("output_validators").join(VALIDATOR_NAME);
+        tokio::fs::create_dir_all(&validator_dir).await?;
+        let checker_exe = self.resolve(&self.problem.checker_exe)?;
+        let checker_dest = validator_dir.join("checker");
+        tokio::fs::copy(&checker_exe, &checker_dest)
+            .await
+            .with_context(|| format!("failed to copy checker {}", checker_exe.display()))?;
+        set_executable(&checker_dest)?;
+        // arguments may refer to package files (e.g. wrapped checker module)
+        let mut args = String::new();
+        for arg in self.problem.checker_cmd.clone() {
+            let arg_path = self.src.join(&arg);
+            if !Path::new(&arg).is_absolute() && arg_path.is_file() {
+                let dest = validator_dir.join(&arg);
+                tokio::fs::create_dir_all(dest.parent().expect("path is not empty")).await?;
+                tokio::fs::copy(&arg_path, &dest)
+                    .await
+                    .with_context(|| format!("failed to copy {}", arg_path.display()))?;
+                set_executable(&dest)?;
+            }
+            args.push(' ');
+            args.push_str(&shell_quote(&arg));
+        }
+        let run_path = validator_dir.join("run");
+        tokio::fs::write(&run_path, RUN_SCRIPT_TPL.replace("{args}", &args)).await?;
+        set_executable(&run_path)?;
+        let build_path = validator_dir.join("build");
+        tokio::fs::write(&build_path, BUILD_SCRIPT).await?;
+        set_executable(&build_path)?;
+        Ok(())
+    }
+
+    /// Writes `problem.yaml` and `.timelimit`
+    async fn export_config(&mut self) -> anyhow::Result<()> {
+        let mut limits = self.problem.tests.iter().map(|test| test.limits);
+        let first = limits.next().unwrap_or_default();
+        if limits.any(|l| l.time() != first.time() || l.memory() != first.memory()) {
+            self.report(Diagnostic::warning(
+                DiagnosticCode::UnsupportedFeature,
+                "tests have different limits, maximal limits are exported",
+            ))
+            .await;
+        }
+        let time_limit = self
+            .problem
+            .tests
+            .iter()
+            .map(|test| test.limits.time())
+            .max()
+            .unwrap_or_else(|| first.time());
+        let memory_limit = self
+            .problem
+            .tests
+            .iter()
+            .map(|test| test.limits.memory())
+            .max()
+            .unwrap_or_else(|| first.memory());
+        let time_limit_sec = time_limit as f64 / 1000.0;
+
+        let mut limits = serde_yaml::Mapping::new();
+        // Kattis wants memory limit in MiB
+        limits.insert(
+            "memory".into(),
+            ((memory_limit + (1 << 20) - 1) >> 20).into(),
+        );
+        limits.insert("time_limit".into(), time_limit_sec.into());
+        let mut config = serde_yaml::Mapping::new();
+        config.insert("name".into(), self.problem.title.clone().into());
+        config.insert("validation".into(), "custom".into());
+        config.insert("limits".into(), limits.into());
+        tokio::fs::write(
+            self.dest.join("problem.yaml"),
+            serde_yaml::to_string(&config)?,
+        )
+        .await?;
+        // DOMjudge reads time limit from this file
+        tokio::fs::write(
+            self.dest.join(".timelimit"),
+            format!("{}\n", time_limit_sec),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+pub(super) async fn export(
+    src: &Path,
+    dest: &Path,
+    pw: &mut ProgressWriter<ExportUpdate>,
+) -> anyhow::Result<()> {
+    let manifest_path = src.join("manifest.json");
+    let manifest = tokio::fs::read(&manifest_path)
+        .await
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let problem: pom::Problem =
+        serde_json::from_slice(&manifest).context("manifest.json parse error")?;
+    let mut exporter = Exporter {
+        src,
+        dest,
+        problem,
+        pw,
+    };
+    exporter.export_config().await?;
+    exporter.export_tests().await?;
+    exporter.export_checker().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoting() {
+        assert_eq!(shell_quote("--eps"), "'--eps'");
+        assert_eq!(shell_quote("it's"), r#"'it'\''s'"#);
+    }
+}
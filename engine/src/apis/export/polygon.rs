@@ -0,0 +1,660 @@
+//! Exports source package into Polygon package format (`problem.xml`).
+//! Generated tests are exported together with generator invocations,
+//! so that Polygon can regenerate them.
+use super::ExportUpdate;
+use crate::{
+    apis::import::{Diagnostic, DiagnosticCode},
+    manifest::{Check, TestGenSpec},
+    operation::ProgressWriter,
+};
+use anyhow::Context as _;
+use std::{fmt::Write as _, path::Path};
+
+/// Source type of all exported C++ files
+const CPP_SOURCE_TYPE: &str = "cpp.g++17";
+
+/// Polygon solution tags for verdict prefixes of solution names,
+/// e.g. `wrong-answer-greedy` is tagged `wrong-answer`.
+/// Solutions without known prefix are tagged `accepted`.
+const SOLUTION_TAGS: &[(&str, &str)] = &[
+    ("accepted", "accepted"),
+    ("wrong-answer", "wrong-answer"),
+    ("time-limit-exceeded", "time-limit-exceeded"),
+    ("memory-limit-exceeded", "memory-limit-exceeded"),
+    ("run-time-error", "rejected"),
+];
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn feedback_policy(feedback: &svaluer::cfg::FeedbackKind) -> &'static str {
+    match feedback {
+        svaluer::cfg::FeedbackKind::Hidden => "none",
+        svaluer::cfg::FeedbackKind::Brief => "icpc",
+        svaluer::cfg::FeedbackKind::Full => "complete",
+        // Polygon has no fine-grained feedback, so closest policy is chosen
+        svaluer::cfg::FeedbackKind::Custom(custom) => match custom.shown_tests {
+            svaluer::cfg::ShownTests::FirstFailed => "icpc",
+            svaluer::cfg::ShownTests::All if !custom.tests.is_empty() => "complete",
+            _ if custom.score => "points",
+            _ => "none",
+        },
+    }
+}
+
+/// Returns true if group is exported with Polygon `each-test` points policy
+fn scored_per_test(group: &svaluer::cfg::Group) -> bool {
+    group.test_score.is_some() || group.scoring == svaluer::cfg::ScoringPolicy::Partial
+}
+
+/// Splits group points between its `count` tests, so that their sum is what
+/// svaluer gives for the passed group
+fn group_points(group: &svaluer::cfg::Group, count: u32, warnings: &mut Vec<String>) -> Vec<u32> {
+    let spread_score = match group.scoring {
+        svaluer::cfg::ScoringPolicy::Partial => {
+            if group.score.checked_rem(count).unwrap_or(0) != 0 {
+                warnings.push(format!(
+                    "group {}: score {} is not divisible by tests count {}, Polygon will round partial scores differently",
+                    group.name, group.score, count
+                ));
+            }
+            group.score
+        }
+        svaluer::cfg::ScoringPolicy::Complete if scored_per_test(group) => {
+            if group.score != 0 {
+                warnings.push(format!(
+                    "group {}: score {} for passing the whole group can not be combined with per-test points in Polygon, it is not exported",
+                    group.name, group.score
+                ));
+            }
+            0
+        }
+        svaluer::cfg::ScoringPolicy::Complete => group.score,
+    };
+    let test_score = group.test_score.unwrap_or(0);
+    (0..count)
+        .map(|i| {
+            let mut points = test_score + spread_score / count;
+            if i == 0 {
+                points += spread_score % count;
+            }
+            points
+        })
+        .collect()
+}
+
+/// Test as it will be written into `<tests>`
+struct ExportedTest {
+    /// Generator command, None for manual tests
+    cmd: Option<String>,
+    sample: bool,
+    /// Polygon group name
+    group: Option<String>,
+    points: u32,
+}
+
+struct Exporter<'a> {
+    /// Source package root
+    src: &'a Path,
+    dest: &'a Path,
+    problem: crate::manifest::Problem,
+    valuer_cfg: Option<svaluer::Config>,
+    pw: &'a mut ProgressWriter<ExportUpdate>,
+}
+
+impl<'a> Exporter<'a> {
+    async fn report(&mut self, diagnostic: Diagnostic) {
+        self.pw.send(ExportUpdate::Diagnostic(diagnostic)).await;
+    }
+
+    /// Copies single-file C++ program from `dir` into `dest` (relative to output dir).
+    /// Returns false if program can not be exported.
+    async fn export_program(&mut self, dir: &Path, dest: &str) -> anyhow::Result<bool> {
+        let main_path = dir.join("main.cpp");
+        if !main_path.is_file() {
+            let message = format!(
+                "{} does not contain main.cpp, only single-file C++ programs can be exported",
+                dir.display()
+            );
+            self.report(Diagnostic::warning(
+                DiagnosticCode::UnsupportedFeature,
+                message,
+            ))
+            .await;
+            return Ok(false);
+        }
+        let dest = self.dest.join(dest);
+        tokio::fs::create_dir_all(dest.parent().expect("path is not empty")).await?;
+        tokio::fs::copy(&main_path, &dest)
+            .await
+            .with_context(|| format!("failed to copy {}", main_path.display()))?;
+        Ok(true)
+    }
+
+    fn valuer_group_by_tag(&self, tag: &str) -> Option<&svaluer::cfg::Group> {
+        self.valuer_cfg
+            .as_ref()?
+            .groups
+            .iter()
+            .find(|group| group.tests_tag() == tag)
+    }
+
+    /// Copies test files and returns description of each test
+    async fn export_tests(&mut self) -> anyhow::Result<Vec<ExportedTest>> {
+        self.pw
+            .send(ExportUpdate::ExportTests {
+                count: self.problem.tests.len(),
+            })
+            .await;
+        for dir in &["tests", "files"] {
+            tokio::fs::create_dir_all(self.dest.join(dir)).await?;
+        }
+        let sample_tags = super::sample_tags(self.valuer_cfg.as_ref());
+        let src_tests = self.src.join("tests");
+        let mut out = Vec::new();
+        for (idx, test) in self.problem.tests.iter().enumerate() {
+            let test_id = idx + 1;
+            let cmd = match &test.gen {
+                TestGenSpec::Generate { testgen, args } => {
+                    let mut cmd = testgen.clone();
+                    for arg in args {
+                        cmd.push(' ');
+                        cmd.push_str(arg);
+                    }
+                    Some(cmd)
+                }
+                TestGenSpec::File { path } => {
+                    let input = src_tests.join(path);
+                    tokio::fs::copy(&input, self.dest.join(format!("tests/{:0>2}", test_id)))
+                        .await
+                        .with_context(|| format!("failed to copy {}", input.display()))?;
+                    None
+                }
+            };
+            if let Some(answer) = &test.answer {
+                let answer = src_tests.join(answer);
+                tokio::fs::copy(&answer, self.dest.join(format!("tests/{:0>2}.a", test_id)))
+                    .await
+                    .with_context(|| format!("failed to copy {}", answer.display()))?;
+            }
+            let group = self
+                .valuer_group_by_tag(&test.group)
+                .map(|group| group.name.clone());
+            out.push(ExportedTest {
+                cmd,
+                sample: super::is_sample(&sample_tags, &test.group, &test.tags),
+                group,
+                points: 0,
+            });
+        }
+        // Polygon sums test points, so group score is spread over its tests
+        let mut warnings = Vec::new();
+        if let Some(config) = &self.valuer_cfg {
+            for group in &config.groups {
+                let tests: Vec<_> = out
+                    .iter_mut()
+                    .filter(|test| test.group.as_deref() == Some(group.name.as_str()))
+                    .collect();
+                let points = group_points(group, tests.len() as u32, &mut warnings);
+                for (test, points) in tests.into_iter().zip(points) {
+                    test.points = points;
+                }
+            }
+        }
+        for message in warnings {
+            self.report(Diagnostic::warning(
+                DiagnosticCode::UnsupportedFeature,
+                message,
+            ))
+            .await;
+        }
+        let args_with_spaces = self.problem.tests.iter().any(|test| match &test.gen {
+            TestGenSpec::Generate { args, .. } => {
+                args.iter().any(|a| a.contains(char::is_whitespace))
+            }
+            TestGenSpec::File { .. } => false,
+        });
+        if args_with_spaces {
+            self.report(Diagnostic::warning(
+                DiagnosticCode::UnsupportedFeature,
+                "some generator arguments contain spaces, they will be split by Polygon",
+            ))
+            .await;
+        }
+        Ok(out)
+    }
+
+    /// Exports generators and returns their names
+    async fn export_generators(&mut self) -> anyhow::Result<Vec<String>> {
+        let mut used = Vec::new();
+        for test in &self.problem.tests {
+            if let TestGenSpec::Generate { testgen, .. } = &test.gen {
+                if !used.contains(testgen) {
+                    used.push(testgen.clone());
+                }
+            }
+        }
+        let mut out = Vec::new();
+        for name in used {
+            let dir = self.src.join("generators").join(&name);
+            if self
+                .export_program(&dir, &format!("files/{}.cpp", name))
+                .await?
+            {
+                out.push(name);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Exports checker and returns `<checker>` element
+    async fn export_checker(&mut self) -> anyhow::Result<String> {
+        self.pw.send(ExportUpdate::ExportChecker).await;
+        let args = self.problem.check_options.args.clone();
+        let module_dir = match &self.problem.check {
+            Check::Builtin(bc) if bc.name == "polygon-compat" => {
+                // testlib checker is built as module and passed to wrapper
+                let module = args.first().and_then(|arg| {
+                    arg.strip_prefix("assets/module-")
+                        .and_then(|rest| rest.strip_suffix("/bin"))
+                });
+                match module {
+                    Some(module) => self.src.join("modules").join(module),
+                    None => anyhow::bail!("polygon-compat checker does not refer to module"),
+                }
+            }
+            Check::Builtin(bc) => {
+                let standard = crate::apis::import::STANDARD_CHECKERS.iter().find(
+                    |(_, builtin, builtin_args)| {
+                        *builtin == bc.name && builtin_args.iter().eq(args.iter())
+                    },
+                );
+                let name = match standard {
+                    Some((name, _, _)) => name,
+                    None => {
+                        let message = format!(
+                            "builtin checker {} with args {:?} has no Polygon equivalent, std::wcmp.cpp is used",
+                            bc.name, args
+                        );
+                        self.report(Diagnostic::warning(
+                            DiagnosticCode::UnsupportedFeature,
+                            message,
+                        ))
+                        .await;
+                        "std::wcmp.cpp"
+                    }
+                };
+                return Ok(format!(
+                    r#"<checker name="{}" type="testlib"/>"#,
+                    escape(name)
+                ));
+            }
+            Check::Custom(_) => {
+                self.report(Diagnostic::warning(
+                    DiagnosticCode::UnsupportedFeature,
+                    "custom checker uses pps checker protocol and must be ported to testlib",
+                ))
+                .await;
+                self.src.join("checkers")
+            }
+        };
+        if !self.export_program(&module_dir, "files/check.cpp").await? {
+            anyhow::bail!("checker can not be exported");
+        }
+        Ok(format!(
+            r#"<checker type="testlib"><source path="files/check.cpp" type="{}"/></checker>"#,
+            CPP_SOURCE_TYPE
+        ))
+    }
+
+    /// Exports solutions and returns (path, tag) pairs
+    async fn export_solutions(&mut self) -> anyhow::Result<Vec<(String, String)>> {
+        let mut dirs = Vec::new();
+        let mut entries = tokio::fs::read_dir(self.src.join("solutions")).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            dirs.push(entry.path());
+        }
+        dirs.sort();
+        if self.problem.primary_solution.is_none() {
+            self.report(Diagnostic::warning(
+                DiagnosticCode::MissingValue,
+                "problem has no primary solution, exported package has no main solution",
+            ))
+            .await;
+        }
+        let mut out = Vec::new();
+        for dir in dirs {
+            let name = match dir.file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => continue,
+            };
+            let tag = if self.problem.primary_solution.as_deref() == Some(name.as_str()) {
+                "main"
+            } else {
+                SOLUTION_TAGS
+                    .iter()
+                    .find(|(prefix, _)| name.starts_with(&format!("{}-", prefix)))
+                    .map_or("accepted", |(_, tag)| tag)
+            };
+            self.pw
+                .send(ExportUpdate::ExportSolution(name.clone()))
+                .await;
+            let path = format!("solutions/{}.cpp", name);
+            if self.export_program(&dir, &path).await? {
+                out.push((path, tag.to_string()));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Returns `<groups>` element, or empty string if there are no groups
+    async fn groups_xml(&mut self) -> String {
+        let config = match self.valuer_cfg.take() {
+            Some(config) => config,
+            None => return String::new(),
+        };
+        let mut out = String::from("      <groups>\n");
+        for group in &config.groups {
+            if !group.run_to_first_failure {
+                let message = format!(
+                    "group {}: run_to_first_failure=false is not supported by Polygon",
+                    group.name
+                );
+                self.report(Diagnostic::warning(
+                    DiagnosticCode::UnsupportedFeature,
+                    message,
+                ))
+                .await;
+            }
+            write!(
+                out,
+                r#"        <group feedback-policy="{}" name="{}""#,
+                feedback_policy(&group.feedback),
+                escape(&group.name),
+            )
+            .unwrap();
+            if scored_per_test(group) {
+                out.push_str(r#" points-policy="each-test""#);
+            } else {
+                write!(
+                    out,
+                    r#" points="{}.0" points-policy="complete-group""#,
+                    group.score
+                )
+                .unwrap();
+            }
+            if group.deps.is_empty() {
+                out.push_str("/>\n");
+                continue;
+            }
+            out.push_str("><dependencies>");
+            for dep in &group.deps {
+                let dep = config.get_group(dep).map(|idx| &config.groups[idx].name);
+                if let Some(dep) = dep {
+                    write!(out, r#"<dependency group="{}"/>"#, escape(dep)).unwrap();
+                }
+            }
+            out.push_str("</dependencies></group>\n");
+        }
+        out.push_str("      </groups>\n");
+        self.valuer_cfg = Some(config);
+        out
+    }
+
+    async fn limits(&mut self) -> pom::Limits {
+        let first = self
+            .problem
+            .tests
+            .first()
+            .map_or(self.problem.limits, |test| test.limits);
+        let differ = self.problem.tests.iter().any(|test| {
+            test.limits.time() != first.time() || test.limits.memory() != first.memory()
+        });
+        if differ {
+            self.report(Diagnostic::warning(
+                DiagnosticCode::UnsupportedFeature,
+                "tests have different limits, maximal limits are exported",
+            ))
+            .await;
+        }
+        let mut limits = first;
+        for test in &self.problem.tests {
+            limits.time = Some(limits.time().max(test.limits.time()));
+            limits.memory = Some(limits.memory().max(test.limits.memory()));
+        }
+        limits
+    }
+
+    async fn export(&mut self) -> anyhow::Result<()> {
+        let limits = self.limits().await;
+        let tests = self.export_tests().await?;
+        let generators = self.export_generators().await?;
+        let checker = self.export_checker().await?;
+        let solutions = self.export_solutions().await?;
+        let has_testlib = self.src.join("testlib.h").is_file();
+        if has_testlib {
+            tokio::fs::copy(
+                self.src.join("testlib.h"),
+                self.dest.join("files/testlib.h"),
+            )
+            .await
+            .context("failed to copy testlib.h")?;
+        }
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"no\"?>\n");
+        writeln!(
+            xml,
+            r#"<problem revision="1" short-name="{}">"#,
+            escape(&self.problem.name)
+        )
+        .unwrap();
+        writeln!(
+            xml,
+            r#"  <names><name language="english" value="{}"/></names>"#,
+            escape(&self.problem.title)
+        )
+        .unwrap();
+        xml.push_str("  <judging input-file=\"\" output-file=\"\">\n");
+        xml.push_str("    <testset name=\"tests\">\n");
+        writeln!(xml, "      <time-limit>{}</time-limit>", limits.time()).unwrap();
+        writeln!(
+            xml,
+            "      <memory-limit>{}</memory-limit>",
+            limits.memory()
+        )
+        .unwrap();
+        writeln!(xml, "      <test-count>{}</test-count>", tests.len()).unwrap();
+        xml.push_str("      <input-path-pattern>tests/%02d</input-path-pattern>\n");
+        xml.push_str("      <answer-path-pattern>tests/%02d.a</answer-path-pattern>\n");
+        xml.push_str("      <tests>\n");
+        for test in &tests {
+            xml.push_str("        <test");
+            match &test.cmd {
+                Some(cmd) => write!(xml, r#" cmd="{}" method="generated""#, escape(cmd)).unwrap(),
+                None => xml.push_str(r#" method="manual""#),
+            }
+            if test.sample {
+                xml.push_str(r#" sample="true""#);
+            }
+            if let Some(group) = &test.group {
+                write!(
+                    xml,
+                    r#" group="{}" points="{}.0""#,
+                    escape(group),
+                    test.points
+                )
+                .unwrap();
+            }
+            xml.push_str("/>\n");
+        }
+        xml.push_str("      </tests>\n");
+        xml.push_str(&self.groups_xml().await);
+        xml.push_str("    </testset>\n");
+        xml.push_str("  </judging>\n");
+        xml.push_str("  <files>\n");
+        if has_testlib {
+            xml.push_str("    <resources><file path=\"files/testlib.h\"/></resources>\n");
+        }
+        xml.push_str("    <executables>\n");
+        for name in &generators {
+            writeln!(
+                xml,
+                r#"      <executable><source path="files/{}.cpp" type="{}"/></executable>"#,
+                escape(name),
+                CPP_SOURCE_TYPE
+            )
+            .unwrap();
+        }
+        xml.push_str("    </executables>\n");
+        xml.push_str("  </files>\n");
+        xml.push_str("  <assets>\n");
+        writeln!(xml, "    {}", checker).unwrap();
+        xml.push_str("    <solutions>\n");
+        for (path, tag) in &solutions {
+            writeln!(
+                xml,
+                r#"      <solution tag="{}"><source path="{}" type="{}"/></solution>"#,
+                tag,
+                escape(path),
+                CPP_SOURCE_TYPE
+            )
+            .unwrap();
+        }
+        xml.push_str("    </solutions>\n");
+        xml.push_str("  </assets>\n");
+        xml.push_str("</problem>\n");
+        tokio::fs::write(self.dest.join("problem.xml"), xml)
+            .await
+            .context("failed to write problem.xml")?;
+        Ok(())
+    }
+}
+
+fn read_valuer_cfg(
+    src: &Path,
+    problem: &crate::manifest::Problem,
+) -> anyhow::Result<Option<svaluer::Config>> {
+    let path = match &problem.valuer_cfg {
+        Some(path) => src.join(path.trim_start_matches('/')),
+        None => return Ok(None),
+    };
+    let data = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let config = serde_yaml::from_str(&data)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(Some(config))
+}
+
+pub(super) async fn export(
+    src: &Path,
+    dest: &Path,
+    pw: &mut ProgressWriter<ExportUpdate>,
+) -> anyhow::Result<()> {
+    let manifest_path = src.join("problem.toml");
+    let manifest = tokio::fs::read_to_string(&manifest_path)
+        .await
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let raw_problem: crate::manifest::RawProblem =
+        toml::from_str(&manifest).context("problem.toml parse error")?;
+    let (problem, _) = raw_problem.postprocess().context("invalid problem.toml")?;
+    let valuer_cfg = read_valuer_cfg(src, &problem)?;
+    let mut exporter = Exporter {
+        src,
+        dest,
+        problem,
+        valuer_cfg,
+        pw,
+    };
+    exporter.export().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svaluer::cfg::{CustomFeedback, FeedbackKind, ShownTests, TestComponent};
+
+    fn custom(tests: Vec<TestComponent>, shown_tests: ShownTests, score: bool) -> FeedbackKind {
+        FeedbackKind::Custom(CustomFeedback {
+            tests,
+            first_failed_test: Vec::new(),
+            shown_tests,
+            score,
+        })
+    }
+
+    #[test]
+    fn xml_escaping() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(
+            escape(r#"a<b & 'c' > "d""#),
+            "a&lt;b &amp; &apos;c&apos; &gt; &quot;d&quot;"
+        );
+    }
+
+    #[test]
+    fn feedback_policies() {
+        assert_eq!(feedback_policy(&FeedbackKind::Hidden), "none");
+        assert_eq!(feedback_policy(&FeedbackKind::Brief), "icpc");
+        assert_eq!(feedback_policy(&FeedbackKind::Full), "complete");
+        let first_failed = custom(vec![TestComponent::Status], ShownTests::FirstFailed, true);
+        assert_eq!(feedback_policy(&first_failed), "icpc");
+        let all = custom(vec![TestComponent::Status], ShownTests::All, false);
+        assert_eq!(feedback_policy(&all), "complete");
+        let score_only = custom(Vec::new(), ShownTests::All, true);
+        assert_eq!(feedback_policy(&score_only), "points");
+        let nothing = custom(Vec::new(), ShownTests::None, false);
+        assert_eq!(feedback_policy(&nothing), "none");
+    }
+
+    fn group(yaml: &str) -> svaluer::cfg::Group {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn complete_group_points() {
+        let g = group("name: g\nfeedback: full\nscore: 10\n");
+        assert!(!scored_per_test(&g));
+        let mut warnings = Vec::new();
+        assert_eq!(group_points(&g, 3, &mut warnings), vec![4, 3, 3]);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn per_test_group_points() {
+        let g = group("name: g\nfeedback: full\nscore: 0\ntest_score: 2\n");
+        assert!(scored_per_test(&g));
+        let mut warnings = Vec::new();
+        assert_eq!(group_points(&g, 3, &mut warnings), vec![2, 2, 2]);
+        assert!(warnings.is_empty());
+
+        let g = group("name: g\nfeedback: full\nscore: 5\ntest_score: 2\n");
+        assert_eq!(group_points(&g, 2, &mut warnings), vec![2, 2]);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn partial_group_points() {
+        let g = group("name: g\nfeedback: full\nscore: 6\nscoring: partial\ntest_score: 1\n");
+        assert!(scored_per_test(&g));
+        let mut warnings = Vec::new();
+        assert_eq!(group_points(&g, 3, &mut warnings), vec![3, 3, 3]);
+        assert!(warnings.is_empty());
+
+        let g = group("name: g\nfeedback: full\nscore: 7\nscoring: partial\n");
+        assert_eq!(group_points(&g, 2, &mut warnings), vec![4, 3]);
+        assert_eq!(warnings.len(), 1);
+    }
+}
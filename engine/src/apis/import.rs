@@ -1,10 +1,19 @@
+mod contest_import;
+mod ejudge_importer;
+mod kattis_importer;
 mod problem_importer;
+mod serve_cfg;
+mod sync;
 mod template;
 mod valuer_cfg;
 
-use crate::operation::{Operation, ProgressWriter};
+use crate::{
+    manifest::{RawContest, RawProblem},
+    operation::{Operation, ProgressWriter},
+};
 use anyhow::{bail, Context as _};
 use problem_importer::Importer;
+pub(crate) use problem_importer::STANDARD_CHECKERS;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashSet,
@@ -13,26 +22,45 @@ use std::{
 
 #[derive(Serialize, Deserialize)]
 pub struct ImportRequest {
-    /// this path specifies file or files that should be imported
+    /// this path specifies file or files that should be imported.
+    /// It can be a directory or a zip archive (e.g. Polygon package).
     pub src_path: PathBuf,
     /// where to put generated problem source
     pub out_path: PathBuf,
     /// do not check that dest is empty
     pub force: bool,
+    /// import tests and answers stored in package instead of running generators
+    pub static_tests: bool,
+    /// if set, imported problem must have this revision
+    pub revision: Option<u32>,
+    /// name of imported contest. By default it is derived from contest url.
+    pub contest_name: Option<String>,
+    /// update package previously imported into out_path, preserving local edits
+    pub sync: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ImportUpdate {
+    /// Started extracting package archive
+    ExtractArchive,
+    /// Started importing contest problem with given code.
+    /// Following updates relate to this problem until the next `ImportProblem`.
+    ImportProblem(String),
     /// Contains one property of discovered problem.
     /// Each `property_name` will be reported at most once.
     Property {
         property_name: PropertyName,
         property_value: String,
     },
-    /// Contains one warnings. May appear multiple times.
-    Warning(String),
+    /// Contains one problem found in the imported package.
+    /// May appear multiple times. If at least one diagnostic has
+    /// `Severity::Error`, import fails after all problems are reported.
+    Diagnostic(Diagnostic),
     /// Started importing checker
     ImportChecker,
+    /// Checker is a standard one and will be replaced with builtin checker
+    /// with given name
+    BuiltinChecker(String),
     /// Started importing tests
     ImportTests,
     /// Finished importing tests. `count` tests imported.
@@ -45,6 +73,24 @@ pub enum ImportUpdate {
     ImportValuerConfig,
     /// Valuer config was not found, default will be used
     DefaultValuerConfig,
+    /// Valuer config was not found, it will be generated from test groups
+    GenerateValuerConfig,
+    /// File of previously imported package was changed by sync.
+    /// `path` is relative to out_path.
+    SyncFile { path: String, action: SyncAction },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    /// File appeared upstream
+    Added,
+    /// File changed upstream and was replaced with new version
+    Updated,
+    /// File was removed upstream
+    Removed,
+    /// File changed both upstream and locally. Local version is kept,
+    /// upstream version (if any) is saved with `.upstream` suffix.
+    Conflict,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -59,6 +105,94 @@ pub enum PropertyName {
     OutputPathPattern,
     /// Value is problem title.
     ProblemTitle,
+    /// Value is problem revision.
+    Revision,
+    /// Value is contest title.
+    ContestTitle,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Import continues, but imported problem may differ from the original.
+    Warning,
+    /// Import continues to find other problems, but will fail eventually.
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticCode {
+    /// File can not be parsed.
+    SyntaxError,
+    /// Element is not expected at this position and is ignored.
+    UnexpectedElement,
+    /// Required attribute or value is missing.
+    MissingValue,
+    /// Attribute or value is malformed.
+    InvalidValue,
+    /// Package uses feature which can not be imported or exported.
+    UnsupportedFeature,
+    /// Package refers to a file which does not exist.
+    MissingFile,
+    /// Package contents contradict each other.
+    Inconsistency,
+}
+
+/// Position in a file of the imported or exported package
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SourceLocation {
+    /// Path relative to package root
+    pub file: String,
+    /// 1-based line number
+    pub line: u32,
+    /// 1-based column number
+    pub column: u32,
+}
+
+/// Describes single problem found in the imported or exported package
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+    pub location: Option<SourceLocation>,
+}
+
+impl Diagnostic {
+    pub(crate) fn warning(code: DiagnosticCode, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            code,
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    pub(crate) fn error(code: DiagnosticCode, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    pub(crate) fn at(mut self, location: SourceLocation) -> Diagnostic {
+        self.location.replace(location);
+        self
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(loc) = &self.location {
+            write!(f, "{}:{}:{}: ", loc.file, loc.line, loc.column)?;
+        }
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{} [{:?}]: {}", severity, self.code, self.message)
+    }
 }
 
 /// Executes ImportRequest
@@ -73,23 +207,163 @@ pub fn exec(req: ImportRequest) -> Operation<ImportUpdate> {
 }
 
 async fn do_exec(req: ImportRequest, tx: &mut ProgressWriter<ImportUpdate>) -> anyhow::Result<()> {
-    match detect_import_kind(&req.src_path)? {
-        ImportKind::Problem => (),
-        ImportKind::Contest => anyhow::bail!("TODO: import contests"),
+    if req.sync && req.force {
+        anyhow::bail!("sync and force can not be used together");
     }
-    import_problem(&req.src_path, &req.out_path, tx).await?;
+    // when syncing, package is imported into temporary dir and then merged
+    let mut sync_dir = None;
+    let out_path = if req.sync {
+        let dir = tempfile::Builder::new()
+            .prefix("pps-sync-")
+            .tempdir()
+            .context("failed to create temporary dir")?;
+        let path = dir.path().to_path_buf();
+        sync_dir.replace(dir);
+        path
+    } else {
+        req.out_path.clone()
+    };
+    // extracted files are removed when this is dropped
+    let mut extracted_archive = None;
+    let src_path = if is_archive(&req.src_path) {
+        tx.send(ImportUpdate::ExtractArchive).await;
+        let dir = extract_archive(&req.src_path).await?;
+        let path = dir.path().to_path_buf();
+        extracted_archive.replace(dir);
+        path
+    } else {
+        req.src_path.clone()
+    };
+    match detect_import_kind(&src_path)? {
+        ImportKind::Problem => {
+            if req.contest_name.is_some() {
+                bail!("contest name can only be specified when importing contest");
+            }
+            import_problem(&src_path, &out_path, req.static_tests, req.revision, tx).await?;
+        }
+        ImportKind::Contest | ImportKind::EjudgeContest | ImportKind::KattisProblem
+            if req.revision.is_some() =>
+        {
+            bail!("revision can only be checked when importing Polygon problem");
+        }
+        ImportKind::KattisProblem => {
+            if req.contest_name.is_some() {
+                bail!("contest name can only be specified when importing contest");
+            }
+            let name = kattis_problem_name(&req.src_path)
+                .context("problem name can not be derived from package path")?;
+            let mut importer = kattis_importer::Importer::new(&src_path, &out_path, name, tx);
+            importer.run().await?;
+            write_problem_manifest(&out_path, &importer.problem_cfg)?;
+        }
+        ImportKind::EjudgeContest => {
+            ejudge_importer::import(&src_path, &out_path, req.contest_name.as_deref(), tx).await?;
+        }
+        ImportKind::Contest => {
+            contest_import::import(
+                &src_path,
+                &out_path,
+                req.contest_name.as_deref(),
+                req.static_tests,
+                tx,
+            )
+            .await?;
+        }
+    }
+    drop(extracted_archive);
+    if sync_dir.is_some() {
+        sync::apply(&out_path, &req.out_path, tx).await?;
+    } else {
+        sync::write_state(&out_path)?;
+    }
+    drop(sync_dir);
 
     Ok(())
 }
 
+fn is_archive(path: &Path) -> bool {
+    path.is_file() && path.extension() == Some(std::ffi::OsStr::new("zip"))
+}
+
+/// Kattis problem name is the name of package directory or archive
+fn kattis_problem_name(src_path: &Path) -> Option<String> {
+    let path = src_path.canonicalize().ok()?;
+    let name = if is_archive(&path) {
+        path.file_stem()?
+    } else {
+        path.file_name()?
+    };
+    Some(name.to_string_lossy().to_string())
+}
+
+/// Extracts zip archive into new temporary directory
+async fn extract_archive(path: &Path) -> anyhow::Result<tempfile::TempDir> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let dir = tempfile::Builder::new()
+            .prefix("pps-import-")
+            .tempdir()
+            .context("failed to create temporary dir")?;
+        let file = std::fs::File::open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .with_context(|| format!("{} is not a valid zip archive", path.display()))?;
+        archive
+            .extract(dir.path())
+            .with_context(|| format!("failed to extract {}", path.display()))?;
+        Ok(dir)
+    })
+    .await
+    .unwrap()
+}
+
 async fn import_problem(
     src: &Path,
     dest: &Path,
+    static_tests: bool,
+    revision: Option<u32>,
     pw: &mut ProgressWriter<ImportUpdate>,
 ) -> anyhow::Result<()> {
     let manifest_path = src.join("problem.xml");
     let manifest = std::fs::read_to_string(manifest_path).context("failed read problem.xml")?;
-    let doc = roxmltree::Document::parse(&manifest).context("parse error")?;
+    let doc = match roxmltree::Document::parse(&manifest) {
+        Ok(doc) => doc,
+        Err(err) => {
+            let pos = err.pos();
+            let diagnostic = Diagnostic::error(DiagnosticCode::SyntaxError, err.to_string()).at(
+                SourceLocation {
+                    file: "problem.xml".to_string(),
+                    line: pos.row,
+                    column: pos.col,
+                },
+            );
+            pw.send(ImportUpdate::Diagnostic(diagnostic.clone())).await;
+            bail!("{}", diagnostic);
+        }
+    };
+
+    if let Some(actual_revision) = doc.root_element().attribute("revision") {
+        pw.send(ImportUpdate::Property {
+            property_name: PropertyName::Revision,
+            property_value: actual_revision.to_string(),
+        })
+        .await;
+    }
+    if let Some(expected_revision) = revision {
+        let actual_revision = doc
+            .root_element()
+            .attribute("revision")
+            .context("problem.xml does not specify revision")?
+            .parse::<u32>()
+            .context("problem.xml has invalid revision")?;
+        if actual_revision != expected_revision {
+            bail!(
+                "revision mismatch: expected {}, but package has revision {}",
+                expected_revision,
+                actual_revision
+            );
+        }
+    }
 
     let mut importer = Importer {
         src: &src,
@@ -99,23 +373,82 @@ async fn import_problem(
         doc: doc.root_element(),
         limits: pom::Limits::default(),
         pw,
+        valuer_groups: Vec::new(),
+        custom_valuer_groups: false,
+        static_tests,
+        errors: Vec::new(),
     };
 
     importer.run().await?;
 
+    write_problem_manifest(dest, &importer.problem_cfg)
+}
+
+fn write_problem_manifest(dest: &Path, problem_cfg: &RawProblem) -> anyhow::Result<()> {
     let manifest_path = dest.join("problem.toml");
     let manifest_toml =
-        toml::Value::try_from(importer.problem_cfg.clone()).context("serialize ppc config")?;
+        toml::Value::try_from(problem_cfg.clone()).context("serialize ppc config")?;
     let manifest_data = toml::ser::to_string_pretty(&manifest_toml)
-        .with_context(|| format!("stringify ppc config: {:#?}", &importer.problem_cfg))?;
+        .with_context(|| format!("stringify ppc config: {:#?}", problem_cfg))?;
     std::fs::write(manifest_path, manifest_data).expect("write ppc manifest");
+    Ok(())
+}
 
+fn write_contest_manifest(dest: &Path, contest: &RawContest) -> anyhow::Result<()> {
+    let manifest_path = dest.join("contest.toml");
+    let manifest_data =
+        toml::ser::to_string_pretty(contest).context("serialize contest manifest")?;
+    std::fs::write(&manifest_path, manifest_data)
+        .with_context(|| format!("write {}", manifest_path.display()))?;
+    Ok(())
+}
+
+fn generate_random_seed() -> anyhow::Result<String> {
+    let mut random_seed = [0; crate::manifest::RANDOM_SEED_LENGTH / 2];
+    getrandom::getrandom(&mut random_seed)?;
+    Ok(hex::encode(random_seed))
+}
+
+/// Fails if some errors were reported during import
+fn check_errors(errors: &[Diagnostic]) -> anyhow::Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let mut message = format!("package has {} errors:", errors.len());
+    for err in errors {
+        message.push('\n');
+        message.push_str(&err.to_string());
+    }
+    bail!(message)
+}
+
+/// Sends diagnostic to client. Errors are also saved to `errors`, so that
+/// import can fail after all problems are reported.
+async fn report(
+    pw: &mut ProgressWriter<ImportUpdate>,
+    errors: &mut Vec<Diagnostic>,
+    diagnostic: Diagnostic,
+) {
+    if diagnostic.severity == Severity::Error {
+        errors.push(diagnostic.clone());
+    }
+    pw.send(ImportUpdate::Diagnostic(diagnostic)).await;
+}
+
+/// Creates directories of problem source layout in `dest`
+fn init_dirs(dest: &Path) -> anyhow::Result<()> {
+    for suf in &["solutions", "generators", "tests", "modules"] {
+        let path = dest.join(suf);
+        std::fs::create_dir(&path).with_context(|| format!("create {}", path.display()))?;
+    }
     Ok(())
 }
 
 enum ImportKind {
     Problem,
     Contest,
+    EjudgeContest,
+    KattisProblem,
 }
 
 fn detect_import_kind(path: &Path) -> anyhow::Result<ImportKind> {
@@ -129,6 +462,82 @@ fn detect_import_kind(path: &Path) -> anyhow::Result<ImportKind> {
     if path.join("contest.xml").exists() {
         return Ok(ImportKind::Contest);
     }
+    if path.join(serve_cfg::FILE_NAME).exists() {
+        return Ok(ImportKind::EjudgeContest);
+    }
+    if path.join("problem.yaml").exists() && path.join("data").is_dir() {
+        return Ok(ImportKind::KattisProblem);
+    }
 
     bail!("unknown src")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+
+    #[tokio::test]
+    async fn archive_extraction() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("package.zip");
+        let mut archive = zip::ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+        archive.add_directory("files/", Default::default()).unwrap();
+        archive
+            .start_file("files/check.cpp", Default::default())
+            .unwrap();
+        archive.write_all(b"int main() {}").unwrap();
+        archive
+            .start_file("problem.xml", Default::default())
+            .unwrap();
+        archive.write_all(b"<problem/>").unwrap();
+        archive.finish().unwrap();
+        assert!(is_archive(&archive_path));
+
+        let extracted = extract_archive(&archive_path).await.unwrap();
+        let read = |name: &str| std::fs::read_to_string(extracted.path().join(name)).unwrap();
+        assert_eq!(read("problem.xml"), "<problem/>");
+        assert_eq!(read("files/check.cpp"), "int main() {}");
+    }
+
+    #[test]
+    fn kattis_names() {
+        let dir = tempfile::tempdir().unwrap();
+        crate::test_util::write(dir.path(), "problem.v2/problem.yaml", "");
+        crate::test_util::write(dir.path(), "hello.zip", "");
+        let name = |path: &str| kattis_problem_name(&dir.path().join(path));
+        assert_eq!(name("problem.v2").as_deref(), Some("problem.v2"));
+        assert_eq!(name("hello.zip").as_deref(), Some("hello"));
+        assert_eq!(name("missing"), None);
+    }
+
+    #[tokio::test]
+    async fn sync_with_force() {
+        let dir = tempfile::tempdir().unwrap();
+        crate::test_util::write(dir.path(), "out/problem.toml", "local");
+        let req = ImportRequest {
+            src_path: dir.path().join("package"),
+            out_path: dir.path().join("out"),
+            force: true,
+            static_tests: false,
+            revision: None,
+            contest_name: None,
+            sync: true,
+        };
+        let mut pw = crate::test_util::discard_progress();
+        let err = do_exec(req, &mut pw).await.unwrap_err();
+        assert_eq!(err.to_string(), "sync and force can not be used together");
+        let local = std::fs::read_to_string(dir.path().join("out/problem.toml")).unwrap();
+        assert_eq!(local, "local");
+    }
+
+    #[tokio::test]
+    async fn invalid_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("package.zip");
+        crate::test_util::write(dir.path(), "package.zip", "not a zip");
+        let err = extract_archive(&archive_path).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("is not a valid zip archive"));
+        assert!(!is_archive(dir.path()));
+    }
+}
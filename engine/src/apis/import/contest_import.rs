@@ -1,64 +1,234 @@
-// TODO revive when contests are reintroduced
-use std::{borrow::Cow, path::Path};
-use thiserror::Error;
-#[derive(Error, Debug)]
-pub(super) enum ImportContestError {
-    #[error("io error: {0}")]
-    Io(#[from] std::io::Error),
-    #[error("syntax error: {0}")]
-    XmlSyn(#[from] roxmltree::Error),
-    #[error("bad context.xml: {0}")]
-    BadManifest(Cow<'static, str>),
+use super::{ImportUpdate, PropertyName};
+use crate::{
+    manifest::{ContestProblem, RawContest},
+    operation::ProgressWriter,
+};
+use anyhow::Context as _;
+use std::path::Path;
+
+/// Problem referenced by contest.xml
+struct ProblemBinding {
+    index: String,
+    name: String,
 }
 
-fn go(node: roxmltree::Node, cfg: &mut entity::Contest) -> Result<(), ImportContestError> {
-    match node.tag_name().name() {
-        "problem" => {
-            let index = node.attribute("index").ok_or_else(|| {
-                ImportContestError::BadManifest("index attribute missing in <problem />".into())
-            })?;
-            let url = node.attribute("url").ok_or_else(|| {
-                ImportContestError::BadManifest("url attribute missing in <problem />".into())
-            })?;
-            let problem_name = url
-                .rsplit('/')
-                .next()
-                .expect("rsplit() should never be empty");
-            let binding = entity::entities::contest::ProblemBinding {
-                name: problem_name.to_string(),
-                code: index.to_string(),
-            };
-            cfg.problems.push(binding);
-            Ok(())
+fn find_title(root: roxmltree::Node) -> Option<String> {
+    let names = root
+        .children()
+        .find(|child| child.tag_name().name() == "names")?;
+    let mut title = None;
+    for name in names.children().filter(|child| child.is_element()) {
+        let value = match name.attribute("value") {
+            Some(v) => v,
+            None => continue,
+        };
+        if name.attribute("main") == Some("true") {
+            return Some(value.to_string());
         }
-        _ => {
-            for child in node.children() {
-                go(child, cfg)?;
-            }
-            Ok(())
+        if title.is_none() {
+            title.replace(value.to_string());
         }
     }
+    title
 }
 
-pub(super) fn import(
-    path: &Path,
-    contest_name: &str,
-) -> Result<entity::Contest, ImportContestError> {
-    let data = std::fs::read_to_string(path)?;
-    let doc = roxmltree::Document::parse(&data)?;
-    let mut cfg = entity::Contest {
-        title: "".to_string(),
-        id: contest_name.to_string(),
-        problems: vec![],
-        judges: vec![],
-        group: vec![],
-        unregistered_visible: false,
-        anon_visible: false,
-        duration: None,
-        end_time: None,
-        start_time: None,
-        is_virtual: false,
+fn find_problems(root: roxmltree::Node) -> anyhow::Result<Vec<ProblemBinding>> {
+    let problems = root
+        .children()
+        .find(|child| child.tag_name().name() == "problems")
+        .context("contest.xml does not have <problems> element")?;
+    let mut bindings = Vec::new();
+    for node in problems.children().filter(|child| child.is_element()) {
+        if node.tag_name().name() != "problem" {
+            continue;
+        }
+        let index = node
+            .attribute("index")
+            .context("index attribute missing in <problem />")?;
+        let url = node
+            .attribute("url")
+            .context("url attribute missing in <problem />")?;
+        let name = url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .expect("rsplit() should never be empty");
+        bindings.push(ProblemBinding {
+            index: index.to_string(),
+            name: name.to_string(),
+        });
+    }
+    Ok(bindings)
+}
+
+/// Imports Polygon contest package.
+/// Each problem is imported into `dest/<index>`, and `dest/contest.toml`
+/// lists problems in contest order.
+pub(super) async fn import(
+    src: &Path,
+    dest: &Path,
+    contest_name: Option<&str>,
+    static_tests: bool,
+    pw: &mut ProgressWriter<ImportUpdate>,
+) -> anyhow::Result<()> {
+    let data =
+        std::fs::read_to_string(src.join("contest.xml")).context("failed read contest.xml")?;
+    let (title, url, bindings) = {
+        let doc = roxmltree::Document::parse(&data).context("contest.xml is not valid xml")?;
+        let root = doc.root_element();
+        (
+            find_title(root),
+            root.attribute("url").map(ToString::to_string),
+            find_problems(root)?,
+        )
+    };
+    let name = match contest_name {
+        Some(name) => name.to_string(),
+        None => url
+            .as_deref()
+            .and_then(|url| url.trim_end_matches('/').rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .context("contest name is not specified and can not be derived from contest.xml")?
+            .to_string(),
+    };
+    let title = title.unwrap_or_else(|| name.clone());
+    pw.send(ImportUpdate::Property {
+        property_name: PropertyName::ContestTitle,
+        property_value: title.clone(),
+    })
+    .await;
+
+    let mut contest = RawContest {
+        title,
+        name,
+        problems: Vec::new(),
     };
-    go(doc.root(), &mut cfg)?;
-    Ok(cfg)
+    let mut failed = Vec::new();
+    for binding in bindings {
+        pw.send(ImportUpdate::ImportProblem(binding.index.clone()))
+            .await;
+        let problem_src = src.join("problems").join(&binding.index);
+        let problem_dest = dest.join(&binding.index);
+        let res = async {
+            std::fs::create_dir_all(&problem_dest)
+                .with_context(|| format!("create {}", problem_dest.display()))?;
+            super::import_problem(&problem_src, &problem_dest, static_tests, None, pw).await
+        }
+        .await;
+        if let Err(err) = res {
+            failed.push(format!("{}: {:#}", binding.index, err));
+            continue;
+        }
+        contest.problems.push(ContestProblem {
+            code: binding.index.clone(),
+            name: binding.name,
+            path: binding.index,
+        });
+    }
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "failed to import {} problems:\n{}",
+            failed.len(),
+            failed.join("\n")
+        );
+    }
+
+    super::write_contest_manifest(dest, &contest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{collect_progress, write};
+
+    fn problem_xml(title: &str) -> String {
+        format!(
+            r#"<problem revision="3">
+    <names>
+        <name language="english" value="{}"/>
+    </names>
+</problem>"#,
+            title
+        )
+    }
+
+    /// Creates contest package with problems `b` and `a`, in this order
+    fn contest_package() -> tempfile::TempDir {
+        let src = tempfile::tempdir().unwrap();
+        write(
+            src.path(),
+            "contest.xml",
+            r#"<contest url="https://polygon.codeforces.com/c/1234/spring-cup">
+    <names>
+        <name language="russian" value="Весенний кубок"/>
+        <name language="english" main="true" value="Spring Cup"/>
+    </names>
+    <problems>
+        <problem index="b" url="https://polygon.codeforces.com/p/jury/sum"/>
+        <problem index="a" url="https://polygon.codeforces.com/p/jury/product/"/>
+    </problems>
+</contest>"#,
+        );
+        for (index, title) in &[("b", "Sum"), ("a", "Product")] {
+            let problem_dir = src.path().join("problems").join(index);
+            write(&problem_dir, "problem.xml", &problem_xml(title));
+            write(&problem_dir, "files/testlib.h", "");
+        }
+        src
+    }
+
+    async fn run_import(
+        src: &Path,
+        dest: &Path,
+        contest_name: Option<&str>,
+    ) -> (anyhow::Result<()>, Vec<ImportUpdate>) {
+        collect_progress(
+            |mut pw| async move { import(src, dest, contest_name, false, &mut pw).await },
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn contest_layout() {
+        let src = contest_package();
+        let dest = tempfile::tempdir().unwrap();
+        let (res, updates) = run_import(src.path(), dest.path(), None).await;
+        res.unwrap();
+        let imported: Vec<_> = updates
+            .iter()
+            .filter_map(|update| match update {
+                ImportUpdate::ImportProblem(index) => Some(index.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(imported, ["b", "a"]);
+        for index in &["a", "b"] {
+            assert!(dest.path().join(index).join("problem.toml").exists());
+        }
+
+        let manifest = std::fs::read_to_string(dest.path().join("contest.toml")).unwrap();
+        let contest: RawContest = toml::from_str(&manifest).unwrap();
+        assert_eq!(contest.title, "Spring Cup");
+        assert_eq!(contest.name, "spring-cup");
+        let problems: Vec<_> = contest
+            .problems
+            .iter()
+            .map(|p| (p.code.as_str(), p.name.as_str(), p.path.as_str()))
+            .collect();
+        assert_eq!(problems, [("b", "sum", "b"), ("a", "product", "a")]);
+    }
+
+    #[tokio::test]
+    async fn failed_problem() {
+        let src = contest_package();
+        std::fs::remove_file(src.path().join("problems/a/problem.xml")).unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let (res, _) = run_import(src.path(), dest.path(), Some("cup")).await;
+        let err = format!("{:#}", res.unwrap_err());
+        assert!(err.contains("failed to import 1 problems"), "{}", err);
+        assert!(err.contains("a: failed read problem.xml"), "{}", err);
+        // other problems are still imported, but contest manifest is not written
+        assert!(dest.path().join("b/problem.toml").exists());
+        assert!(!dest.path().join("contest.toml").exists());
+    }
 }
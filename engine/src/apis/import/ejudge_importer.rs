@@ -0,0 +1,1000 @@
+//! Importer for ejudge contest directories.
+//! Contest is described by `conf/serve.cfg`, each `[problem]` section
+//! is imported into separate pps problem.
+use super::{
+    serve_cfg::{self, Entry, Section},
+    Diagnostic, DiagnosticCode, ImportUpdate, PropertyName,
+};
+use crate::{
+    manifest::{ContestProblem, RawContest, RawProblem, RawTestsSpec},
+    operation::ProgressWriter,
+};
+use anyhow::Context as _;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Ejudge standard checkers which can be replaced with `cmp-tokens`.
+/// Each entry is (ejudge name, builtin checker args).
+const STANDARD_CHECKERS: &[(&str, &[&str])] = &[
+    ("cmp_file", &[]),
+    ("cmp_file_nospace", &[]),
+    ("cmp_int", &[]),
+    ("cmp_int_seq", &[]),
+    ("cmp_long_long", &[]),
+    ("cmp_long_long_seq", &[]),
+    ("cmp_unsigned_int", &[]),
+    ("cmp_unsigned_int_seq", &[]),
+    ("cmp_unsigned_long_long", &[]),
+    ("cmp_unsigned_long_long_seq", &[]),
+    ("cmp_huge_int", &[]),
+    ("cmp_yesno", &["--ignore-case"]),
+];
+
+/// Ejudge standard checkers which are stricter than `cmp-tokens`:
+/// they compare whole files, including whitespace
+const EXACT_CHECKERS: &[&str] = &["cmp_file"];
+
+/// Ejudge standard checkers comparing floats with precision given by `EPS`
+/// variable of `checker_env`
+const FLOAT_CHECKERS: &[&str] = &[
+    "cmp_double",
+    "cmp_double_seq",
+    "cmp_long_double",
+    "cmp_long_double_seq",
+];
+
+/// Global options which configure contest layout. Problem options with
+/// same names have different meaning, so they are not inherited.
+const LAYOUT_OPTIONS: &[&str] = &["test_dir", "corr_dir", "checker_dir"];
+
+/// `[problem]` section together with its abstract problem
+struct ProblemSection<'a> {
+    own: &'a Section,
+    parent: Option<&'a Section>,
+    /// Global section, which provides defaults for all problems
+    global: &'a Section,
+    short_name: String,
+}
+
+impl<'a> ProblemSection<'a> {
+    fn entry(&self, key: &str) -> Option<&'a Entry> {
+        self.own
+            .get(key)
+            .or_else(|| self.parent.and_then(|p| p.get(key)))
+            .or_else(|| {
+                if LAYOUT_OPTIONS.contains(&key) {
+                    None
+                } else {
+                    self.global.get(key)
+                }
+            })
+    }
+
+    /// Returns option value, with `%Ps` replaced by problem short name
+    fn get(&self, key: &str) -> Option<String> {
+        let value = self.entry(key)?.value.as_deref()?;
+        Some(value.replace("%Ps", &self.short_name))
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        match self.entry(key) {
+            Some(entry) => !matches!(entry.value.as_deref(), Some(v) if v.trim() == "0"),
+            None => false,
+        }
+    }
+
+    fn line(&self, key: &str) -> u32 {
+        self.entry(key).map_or(self.own.line, |entry| entry.line)
+    }
+}
+
+/// Directories of contest, resolved from global options
+struct ContestLayout {
+    /// Contest root
+    root: PathBuf,
+    advanced_layout: bool,
+    test_dir: PathBuf,
+    checker_dir: PathBuf,
+}
+
+impl ContestLayout {
+    fn new(root: &Path, global: &Section) -> ContestLayout {
+        let dir = |key: &str, default: &str| {
+            let value = global
+                .get(key)
+                .and_then(|entry| entry.value.as_deref())
+                .unwrap_or(default);
+            // relative paths are resolved against `conf` dir
+            root.join("conf").join(value)
+        };
+        ContestLayout {
+            root: root.to_path_buf(),
+            advanced_layout: global.get("advanced_layout").is_some(),
+            test_dir: dir("test_dir", "../tests"),
+            checker_dir: dir("checker_dir", "../checkers"),
+        }
+    }
+
+    /// Returns path relative to contest root, used in diagnostics
+    fn display(&self, path: &Path) -> String {
+        let path = path.strip_prefix(&self.root).unwrap_or(path);
+        let mut parts: Vec<&std::ffi::OsStr> = Vec::new();
+        for component in path.components() {
+            match component {
+                std::path::Component::ParentDir => {
+                    parts.pop();
+                }
+                std::path::Component::CurDir => (),
+                other => parts.push(other.as_os_str()),
+            }
+        }
+        parts
+            .iter()
+            .map(|part| part.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+struct ProblemImporter<'a> {
+    problem: ProblemSection<'a>,
+    layout: &'a ContestLayout,
+    dest: &'a Path,
+    pw: &'a mut ProgressWriter<ImportUpdate>,
+    problem_cfg: RawProblem,
+    /// All reported errors. If not empty, import fails.
+    errors: Vec<Diagnostic>,
+}
+
+impl<'a> ProblemImporter<'a> {
+    async fn report(&mut self, diagnostic: Diagnostic) {
+        super::report(self.pw, &mut self.errors, diagnostic).await;
+    }
+
+    async fn report_option(&mut self, key: &str, diagnostic: Diagnostic) {
+        let location = serve_cfg::location(self.problem.line(key));
+        self.report(diagnostic.at(location)).await;
+    }
+
+    /// Directory with problem files in advanced layout
+    fn problem_dir(&self) -> PathBuf {
+        let name = self
+            .problem
+            .get("problem_dir")
+            .or_else(|| self.problem.get("internal_name"))
+            .unwrap_or_else(|| self.problem.short_name.clone());
+        self.layout.root.join("problems").join(name)
+    }
+
+    fn tests_dir(&self) -> PathBuf {
+        if self.layout.advanced_layout {
+            return self.problem_dir().join("tests");
+        }
+        let test_dir = self
+            .problem
+            .get("test_dir")
+            .unwrap_or_else(|| self.problem.short_name.clone());
+        self.layout.test_dir.join(test_dir)
+    }
+
+    fn checker_dir(&self) -> PathBuf {
+        if self.layout.advanced_layout {
+            self.problem_dir()
+        } else {
+            self.layout.checker_dir.clone()
+        }
+    }
+
+    /// Returns printf-style pattern of test files.
+    /// Unless pattern is given explicitly, ejudge names tests like `001.dat`.
+    fn pattern(&self, pat_key: &str, sfx_key: &str, default_sfx: &str) -> String {
+        match self.problem.get(pat_key) {
+            Some(pat) => pat,
+            None => {
+                let sfx = self
+                    .problem
+                    .get(sfx_key)
+                    .unwrap_or_else(|| default_sfx.to_string());
+                format!("%03d{}", sfx)
+            }
+        }
+    }
+
+    /// Parses option value, reporting an error if it is malformed
+    async fn parse_option<T: std::str::FromStr>(&mut self, key: &str) -> Option<T> {
+        let value = self.problem.get(key)?;
+        let parsed = value.trim().parse::<T>().ok();
+        if parsed.is_none() {
+            let message = format!("invalid {}: {}", key, value);
+            self.report_option(
+                key,
+                Diagnostic::error(DiagnosticCode::InvalidValue, message),
+            )
+            .await;
+        }
+        parsed
+    }
+
+    async fn import_limits(&mut self) -> pom::Limits {
+        let mut limits = pom::Limits::default();
+        let time_limit = if self.problem.entry("time_limit_millis").is_some() {
+            self.parse_option::<u64>("time_limit_millis").await
+        } else {
+            self.parse_option::<u64>("time_limit")
+                .await
+                .map(|sec| sec * 1000)
+        };
+        if let Some(tl) = time_limit {
+            limits.time.replace(tl);
+            self.pw
+                .send(ImportUpdate::Property {
+                    property_name: PropertyName::TimeLimit,
+                    property_value: tl.to_string(),
+                })
+                .await;
+        }
+        if let Some(value) = self.problem.get("max_vm_size") {
+            match serve_cfg::parse_size(&value) {
+                Some(ml) => {
+                    limits.memory.replace(ml);
+                    self.pw
+                        .send(ImportUpdate::Property {
+                            property_name: PropertyName::MemoryLimit,
+                            property_value: ml.to_string(),
+                        })
+                        .await;
+                }
+                None => {
+                    let message = format!("invalid max_vm_size: {}", value);
+                    self.report_option(
+                        "max_vm_size",
+                        Diagnostic::error(DiagnosticCode::InvalidValue, message),
+                    )
+                    .await;
+                }
+            }
+        }
+        limits
+    }
+
+    async fn import_checker(&mut self) -> anyhow::Result<()> {
+        self.pw.send(ImportUpdate::ImportChecker).await;
+        if let Some(name) = self.problem.get("standard_checker") {
+            let mut args = STANDARD_CHECKERS
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, args)| args.iter().map(ToString::to_string).collect::<Vec<_>>());
+            if FLOAT_CHECKERS.contains(&name.as_str()) {
+                let env = self.problem.get("checker_env").unwrap_or_default();
+                let eps = env
+                    .split(';')
+                    .find_map(|var| var.trim().strip_prefix("EPS="))
+                    .map(ToString::to_string);
+                match eps {
+                    Some(eps) => args = Some(vec!["--epsilon".to_string(), eps]),
+                    None => {
+                        let message = format!("checker {} requires EPS in checker_env", name);
+                        self.report_option(
+                            "standard_checker",
+                            Diagnostic::error(DiagnosticCode::MissingValue, message),
+                        )
+                        .await;
+                        return Ok(());
+                    }
+                }
+            }
+            let args = match args {
+                Some(args) => args,
+                None => {
+                    let message = format!("standard checker {} is not supported", name);
+                    self.report_option(
+                        "standard_checker",
+                        Diagnostic::error(DiagnosticCode::UnsupportedFeature, message),
+                    )
+                    .await;
+                    return Ok(());
+                }
+            };
+            if EXACT_CHECKERS.contains(&name.as_str()) {
+                let message = format!(
+                    "checker {} compares files exactly, but cmp-tokens ignores whitespace differences",
+                    name
+                );
+                self.report_option(
+                    "standard_checker",
+                    Diagnostic::warning(DiagnosticCode::UnsupportedFeature, message),
+                )
+                .await;
+            }
+            let builtin_name = "cmp-tokens";
+            self.pw
+                .send(ImportUpdate::BuiltinChecker(builtin_name.to_string()))
+                .await;
+            self.problem_cfg.builtin_check = Some(crate::manifest::BuiltinCheck {
+                name: builtin_name.to_string(),
+            });
+            self.problem_cfg.check_options = Some(crate::manifest::CheckOptions { args });
+            return Ok(());
+        }
+        let check_cmd = match self.problem.get("check_cmd") {
+            Some(cmd) => cmd,
+            None => {
+                let message = "problem has neither standard_checker nor check_cmd";
+                self.report(
+                    Diagnostic::error(DiagnosticCode::MissingValue, message)
+                        .at(serve_cfg::location(self.problem.own.line)),
+                )
+                .await;
+                return Ok(());
+            }
+        };
+        let checker_dir = self.checker_dir();
+        let src_path = ["cpp", "cc", "cxx"]
+            .iter()
+            .map(|ext| checker_dir.join(format!("{}.{}", check_cmd, ext)))
+            .find(|path| path.is_file());
+        let src_path = match src_path {
+            Some(path) => path,
+            None => {
+                let message = format!(
+                    "C++ source of checker {} not found in {}",
+                    check_cmd,
+                    self.layout.display(&checker_dir)
+                );
+                self.report_option(
+                    "check_cmd",
+                    Diagnostic::error(DiagnosticCode::MissingFile, message),
+                )
+                .await;
+                return Ok(());
+            }
+        };
+        let source = tokio::fs::read_to_string(&src_path)
+            .await
+            .with_context(|| format!("failed to read {}", src_path.display()))?;
+        if !source.contains("testlib.h") {
+            let message = format!(
+                "checker {}: only testlib checkers can be imported",
+                self.layout.display(&src_path)
+            );
+            self.report_option(
+                "check_cmd",
+                Diagnostic::error(DiagnosticCode::UnsupportedFeature, message),
+            )
+            .await;
+            return Ok(());
+        }
+        let testlib_path = [
+            checker_dir.as_path(),
+            &self.problem_dir(),
+            &self.layout.root,
+        ]
+        .iter()
+        .map(|dir| dir.join("testlib.h"))
+        .find(|path| path.is_file());
+        match testlib_path {
+            Some(path) => {
+                tokio::fs::copy(&path, self.dest.join("testlib.h"))
+                    .await
+                    .context("failed to copy testlib.h")?;
+            }
+            None => {
+                let message = "testlib.h not found";
+                self.report(Diagnostic::error(DiagnosticCode::MissingFile, message))
+                    .await;
+            }
+        }
+        tokio::fs::create_dir(self.dest.join("modules/checker"))
+            .await
+            .context("failed to create checker dir")?;
+        tokio::fs::copy(&src_path, self.dest.join("modules/checker/main.cpp"))
+            .await
+            .context("failed to copy checker")?;
+        let cmakefile = self.dest.join("modules/checker/CMakeLists.txt");
+        let cmakedata = super::template::get_checker_cmakefile(super::template::CheckerOptions {});
+        tokio::fs::write(cmakefile, cmakedata)
+            .await
+            .context("write checker's CMakeLists.txt")?;
+        self.problem_cfg.builtin_check = Some(crate::manifest::BuiltinCheck {
+            name: "polygon-compat".to_string(),
+        });
+        self.problem_cfg.check_options = Some(crate::manifest::CheckOptions {
+            args: vec!["assets/module-checker/bin".to_string()],
+        });
+        Ok(())
+    }
+
+    /// Imports valuer config, returning group of each test.
+    /// Returns empty map if problem does not have valuer config.
+    async fn import_valuer_config(&mut self) -> anyhow::Result<HashMap<u32, String>> {
+        let mut candidates = vec![self.tests_dir().join("valuer.cfg")];
+        if self.layout.advanced_layout {
+            candidates.insert(0, self.problem_dir().join("valuer.cfg"));
+        }
+        let valuer_cfg_path = candidates.into_iter().find(|path| path.is_file());
+        let (config, test_groups) = match valuer_cfg_path {
+            Some(path) => {
+                self.pw.send(ImportUpdate::ImportValuerConfig).await;
+                let file = self.layout.display(&path);
+                let imported = super::valuer_cfg::import(&path, &file).await?;
+                for diagnostic in imported.diagnostics {
+                    self.report(diagnostic).await;
+                }
+                (
+                    serde_yaml::to_string(&imported.config)?,
+                    imported.test_groups,
+                )
+            }
+            None => {
+                self.pw.send(ImportUpdate::DefaultValuerConfig).await;
+                let config = include_str!("./default_valuer_config.yaml").to_string();
+                (config, HashMap::new())
+            }
+        };
+        tokio::fs::write(self.dest.join("valuer.yaml"), config).await?;
+        self.problem_cfg.valuer_cfg = Some("valuer.yaml".to_string());
+        Ok(test_groups)
+    }
+
+    async fn import_tests(
+        &mut self,
+        limits: pom::Limits,
+        test_groups: &HashMap<u32, String>,
+    ) -> anyhow::Result<()> {
+        self.pw.send(ImportUpdate::ImportTests).await;
+        let tests_dir = self.tests_dir();
+        let test_pat = self.pattern("test_pat", "test_sfx", ".dat");
+        let corr_pat = self.pattern("corr_pat", "corr_sfx", ".ans");
+        let use_corr = self.problem.flag("use_corr");
+        self.pw
+            .send(ImportUpdate::Property {
+                property_name: PropertyName::InputPathPattern,
+                property_value: test_pat.clone(),
+            })
+            .await;
+        if use_corr {
+            self.pw
+                .send(ImportUpdate::Property {
+                    property_name: PropertyName::OutputPathPattern,
+                    property_value: corr_pat.clone(),
+                })
+                .await;
+        } else {
+            // ejudge does not use correct answers by default
+            let message =
+                "problem does not use correct answers (use_corr), they will be generated by reference solution";
+            self.report_option(
+                "use_corr",
+                Diagnostic::warning(DiagnosticCode::UnsupportedFeature, message),
+            )
+            .await;
+        }
+        // ejudge finds tests by probing files until first missing one
+        let mut groups: Vec<String> = Vec::new();
+        loop {
+            let test_id = groups.len() as u32 + 1;
+            let test_file = crate::manifest::format_test_path(&test_pat, test_id)?;
+            if !tests_dir.join(&test_file).is_file() {
+                break;
+            }
+            let dest_tests_dir = self.dest.join("tests");
+            tokio::fs::copy(tests_dir.join(&test_file), dest_tests_dir.join(&test_file))
+                .await
+                .with_context(|| format!("failed to copy test {}", test_id))?;
+            if use_corr {
+                let corr_file = crate::manifest::format_test_path(&corr_pat, test_id)?;
+                if tests_dir.join(&corr_file).is_file() {
+                    tokio::fs::copy(tests_dir.join(&corr_file), dest_tests_dir.join(&corr_file))
+                        .await
+                        .with_context(|| format!("failed to copy answer of test {}", test_id))?;
+                } else {
+                    let message = format!(
+                        "test {}: {} not found",
+                        test_id,
+                        self.layout.display(&tests_dir.join(&corr_file))
+                    );
+                    self.report(Diagnostic::error(DiagnosticCode::MissingFile, message))
+                        .await;
+                }
+            }
+            let group = if test_groups.is_empty() {
+                "default".to_string()
+            } else {
+                match test_groups.get(&test_id) {
+                    Some(group) => group.clone(),
+                    None => {
+                        let message = format!(
+                            "test {} does not belong to any group of valuer.cfg",
+                            test_id
+                        );
+                        self.report(Diagnostic::error(DiagnosticCode::Inconsistency, message))
+                            .await;
+                        "default".to_string()
+                    }
+                }
+            };
+            groups.push(group);
+        }
+        if groups.is_empty() {
+            let message = format!(
+                "no tests matching {} found in {}",
+                test_pat,
+                self.layout.display(&tests_dir)
+            );
+            self.report(Diagnostic::error(DiagnosticCode::MissingFile, message))
+                .await;
+        }
+        let mut extra_tests = test_groups
+            .keys()
+            .filter(|&&tid| tid as usize > groups.len())
+            .collect::<Vec<_>>();
+        extra_tests.sort_unstable();
+        if let Some(tid) = extra_tests.first() {
+            let message = format!(
+                "valuer.cfg refers to test {}, but only {} tests found",
+                tid,
+                groups.len()
+            );
+            self.report(Diagnostic::warning(DiagnosticCode::Inconsistency, message))
+                .await;
+        }
+        // consecutive tests of one group are described by single spec
+        let mut begin = 0;
+        while begin < groups.len() {
+            let mut end = begin;
+            while end + 1 < groups.len() && groups[end + 1] == groups[begin] {
+                end += 1;
+            }
+            self.problem_cfg.tests.push(RawTestsSpec {
+                map: format!("{}..{}", begin + 1, end + 1),
+                testgen: None,
+                files: Some(test_pat.clone()),
+                answers: if use_corr {
+                    Some(corr_pat.clone())
+                } else {
+                    None
+                },
+                limits,
+                group: groups[begin].clone(),
+                tags: Vec::new(),
+            });
+            begin = end + 1;
+        }
+        self.pw
+            .send(ImportUpdate::ImportTestsDone {
+                count: groups.len(),
+            })
+            .await;
+        Ok(())
+    }
+
+    /// Imports reference solution as primary solution.
+    /// Unless problem uses correct answers, it is required to generate them.
+    async fn import_solution(&mut self) -> anyhow::Result<()> {
+        let use_corr = self.problem.flag("use_corr");
+        let diagnostic: fn(DiagnosticCode, String) -> Diagnostic = if use_corr {
+            Diagnostic::warning
+        } else {
+            Diagnostic::error
+        };
+        let checker_dir = self.checker_dir();
+        let (key, src_path) = if let Some(src) = self.problem.get("solution_src") {
+            let path = checker_dir.join(src);
+            ("solution_src", Some(path).filter(|path| path.is_file()))
+        } else if let Some(cmd) = self.problem.get("solution_cmd") {
+            let path = ["cpp", "cc", "cxx"]
+                .iter()
+                .map(|ext| checker_dir.join(format!("{}.{}", cmd, ext)))
+                .find(|path| path.is_file());
+            ("solution_cmd", path)
+        } else {
+            if !use_corr {
+                let message = "problem does not use correct answers (use_corr), but has neither solution_src nor solution_cmd to generate them";
+                self.report(
+                    diagnostic(DiagnosticCode::MissingValue, message.to_string())
+                        .at(serve_cfg::location(self.problem.own.line)),
+                )
+                .await;
+            }
+            return Ok(());
+        };
+        let src_path = match src_path {
+            Some(path) => path,
+            None => {
+                let message = format!(
+                    "source of reference solution {} not found in {}",
+                    self.problem.get(key).unwrap_or_default(),
+                    self.layout.display(&checker_dir)
+                );
+                self.report_option(key, diagnostic(DiagnosticCode::MissingFile, message))
+                    .await;
+                return Ok(());
+            }
+        };
+        let is_cpp = matches!(
+            src_path.extension().and_then(|ext| ext.to_str()),
+            Some("cpp") | Some("cc") | Some("cxx")
+        );
+        if !is_cpp {
+            let message = format!(
+                "reference solution {}: only C++ solutions can be imported",
+                self.layout.display(&src_path)
+            );
+            self.report_option(key, diagnostic(DiagnosticCode::UnsupportedFeature, message))
+                .await;
+            return Ok(());
+        }
+        self.pw.send(ImportUpdate::ImportSolutions).await;
+        self.pw
+            .send(ImportUpdate::ImportSolution("main".to_string()))
+            .await;
+        let dir = self.dest.join("solutions/main");
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .context("create main solution dir")?;
+        tokio::fs::copy(&src_path, dir.join("main.cpp"))
+            .await
+            .with_context(|| format!("failed to copy {}", src_path.display()))?;
+        tokio::fs::write(dir.join("CMakeLists.txt"), include_str!("./solution.cmake"))
+            .await
+            .context("write CMakeLists.txt for solution")?;
+        self.problem_cfg.primary_solution = Some("main".to_string());
+        Ok(())
+    }
+
+    async fn run(&mut self) -> anyhow::Result<()> {
+        super::init_dirs(self.dest)?;
+        let name = self
+            .problem
+            .get("internal_name")
+            .unwrap_or_else(|| self.problem.short_name.clone());
+        let title = self
+            .problem
+            .get("long_name")
+            .unwrap_or_else(|| name.clone());
+        self.pw
+            .send(ImportUpdate::Property {
+                property_name: PropertyName::ProblemTitle,
+                property_value: title.clone(),
+            })
+            .await;
+        self.problem_cfg.name = name;
+        self.problem_cfg.title = title;
+        self.problem_cfg.valuer = "icpc".to_string();
+        self.problem_cfg.check_type = "builtin".to_string();
+        self.problem_cfg.random_seed = Some(super::generate_random_seed()?);
+
+        let limits = self.import_limits().await;
+        self.problem_cfg.limits = limits;
+        let test_groups = self.import_valuer_config().await?;
+        self.import_tests(limits, &test_groups).await?;
+        self.import_checker().await?;
+        self.import_solution().await?;
+        super::check_errors(&self.errors)
+    }
+}
+
+/// Imports ejudge contest.
+/// Each problem is imported into `dest/<short_name>`, and `dest/contest.toml`
+/// lists problems in the order of serve.cfg.
+pub(super) async fn import(
+    src: &Path,
+    dest: &Path,
+    contest_name: Option<&str>,
+    pw: &mut ProgressWriter<ImportUpdate>,
+) -> anyhow::Result<()> {
+    let data = std::fs::read_to_string(src.join(serve_cfg::FILE_NAME))
+        .with_context(|| format!("failed read {}", serve_cfg::FILE_NAME))?;
+    let (sections, diagnostics) = serve_cfg::parse(&data);
+    let mut errors = Vec::new();
+    for diagnostic in diagnostics {
+        super::report(pw, &mut errors, diagnostic).await;
+    }
+    let name = match contest_name {
+        Some(name) => name.to_string(),
+        None => src
+            .canonicalize()
+            .ok()
+            .and_then(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .context("contest name is not specified and can not be derived from path")?,
+    };
+    pw.send(ImportUpdate::Property {
+        property_name: PropertyName::ContestTitle,
+        property_value: name.clone(),
+    })
+    .await;
+    let layout = ContestLayout::new(src, &sections[0]);
+
+    let problem_sections = sections
+        .iter()
+        .filter(|section| section.name == "problem")
+        .collect::<Vec<_>>();
+    let mut abstract_problems = HashMap::new();
+    let mut problems = Vec::new();
+    for &section in &problem_sections {
+        let short_name = match section.get("short_name").and_then(|e| e.value.clone()) {
+            Some(short_name) => short_name,
+            None => {
+                let message = "[problem] does not have short_name";
+                let diagnostic = Diagnostic::error(DiagnosticCode::MissingValue, message)
+                    .at(serve_cfg::location(section.line));
+                super::report(pw, &mut errors, diagnostic).await;
+                continue;
+            }
+        };
+        if section.get("abstract").is_some() {
+            abstract_problems.insert(short_name, section);
+        } else {
+            problems.push((short_name, section));
+        }
+    }
+    super::check_errors(&errors)?;
+
+    let mut contest = RawContest {
+        title: name.clone(),
+        name,
+        problems: Vec::new(),
+    };
+    let mut failed = Vec::new();
+    for (short_name, section) in problems {
+        pw.send(ImportUpdate::ImportProblem(short_name.clone()))
+            .await;
+        let parent = match section.get("super").and_then(|e| e.value.as_deref()) {
+            Some(parent_name) => match abstract_problems.get(parent_name) {
+                Some(&parent) => Some(parent),
+                None => {
+                    let message = format!("abstract problem {} not found", parent_name);
+                    let diagnostic = Diagnostic::error(DiagnosticCode::InvalidValue, message)
+                        .at(serve_cfg::location(section.get("super").unwrap().line));
+                    pw.send(ImportUpdate::Diagnostic(diagnostic.clone())).await;
+                    failed.push(format!("{}: {}", short_name, diagnostic));
+                    continue;
+                }
+            },
+            None => None,
+        };
+        let problem_dest = dest.join(&short_name);
+        let res = async {
+            std::fs::create_dir_all(&problem_dest)
+                .with_context(|| format!("create {}", problem_dest.display()))?;
+            let mut importer = ProblemImporter {
+                problem: ProblemSection {
+                    own: section,
+                    parent,
+                    global: &sections[0],
+                    short_name: short_name.clone(),
+                },
+                layout: &layout,
+                dest: &problem_dest,
+                pw: &mut *pw,
+                problem_cfg: RawProblem::default(),
+                errors: Vec::new(),
+            };
+            importer.run().await?;
+            super::write_problem_manifest(&problem_dest, &importer.problem_cfg)?;
+            Ok::<_, anyhow::Error>(importer.problem_cfg.name)
+        }
+        .await;
+        match res {
+            Ok(problem_name) => contest.problems.push(ContestProblem {
+                code: short_name.clone(),
+                name: problem_name,
+                path: short_name,
+            }),
+            Err(err) => failed.push(format!("{}: {:#}", short_name, err)),
+        }
+    }
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "failed to import {} problems:\n{}",
+            failed.len(),
+            failed.join("\n")
+        );
+    }
+
+    super::write_contest_manifest(dest, &contest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        apis::import::Severity,
+        test_util::{collect_progress, write},
+    };
+
+    #[test]
+    fn abstract_problem_inheritance() {
+        let (sections, diagnostics) = serve_cfg::parse(
+            r#"
+[problem]
+abstract
+short_name = "Generic"
+input_file = "%Ps.in"
+time_limit = 1
+use_stdin
+
+[problem]
+short_name = "A"
+super = "Generic"
+time_limit = 2
+use_stdin = 0
+"#,
+        );
+        assert!(diagnostics.is_empty());
+        let problem = ProblemSection {
+            own: &sections[2],
+            parent: Some(&sections[1]),
+            global: &sections[0],
+            short_name: "A".to_string(),
+        };
+        // own options override inherited ones
+        assert_eq!(problem.get("time_limit").as_deref(), Some("2"));
+        assert_eq!(problem.line("time_limit"), 12);
+        assert!(!problem.flag("use_stdin"));
+        // inherited options use short name of the concrete problem
+        assert_eq!(problem.get("input_file").as_deref(), Some("A.in"));
+        assert_eq!(problem.line("input_file"), 5);
+        assert!(problem.flag("abstract"));
+        assert_eq!(problem.get("output_file"), None);
+        assert_eq!(problem.line("output_file"), sections[2].line);
+
+        let generic = ProblemSection {
+            own: &sections[1],
+            parent: None,
+            global: &sections[0],
+            short_name: "Generic".to_string(),
+        };
+        assert!(generic.flag("use_stdin"));
+    }
+
+    #[test]
+    fn layout_display() {
+        let (sections, _) = serve_cfg::parse("test_dir = ../data/tests\n");
+        let layout = ContestLayout::new(Path::new("/contest"), &sections[0]);
+        assert!(!layout.advanced_layout);
+        assert_eq!(
+            layout.display(&layout.test_dir.join("a/001")),
+            "data/tests/a/001"
+        );
+        assert_eq!(layout.display(&layout.checker_dir), "checkers");
+    }
+
+    #[test]
+    fn global_defaults() {
+        let (sections, _) = serve_cfg::parse(
+            r#"test_dir = ../data
+test_sfx = ".in"
+use_corr
+
+[problem]
+short_name = "A"
+test_sfx = ".txt"
+"#,
+        );
+        let problem = ProblemSection {
+            own: &sections[1],
+            parent: None,
+            global: &sections[0],
+            short_name: "A".to_string(),
+        };
+        assert_eq!(problem.get("test_sfx").as_deref(), Some(".txt"));
+        assert!(problem.flag("use_corr"));
+        assert_eq!(problem.line("use_corr"), 3);
+        // global test_dir is contest layout option, not default for problems
+        assert_eq!(problem.get("test_dir"), None);
+    }
+
+    #[tokio::test]
+    async fn contest_import() {
+        let src = tempfile::tempdir().unwrap();
+        let src = src.path();
+        write(
+            src,
+            "conf/serve.cfg",
+            r#"test_dir = ../data
+test_sfx = ".in"
+corr_sfx = ".out"
+use_corr
+
+[problem]
+short_name = "A"
+standard_checker = "cmp_int"
+
+[problem]
+short_name = "B"
+test_dir = "bee"
+use_corr = 0
+standard_checker = "cmp_int"
+solution_cmd = "sol"
+"#,
+        );
+        for name in &[
+            "A/001.in",
+            "A/001.out",
+            "A/002.in",
+            "A/002.out",
+            "bee/001.in",
+        ] {
+            write(src, &format!("data/{}", name), "1\n");
+        }
+        write(src, "checkers/sol.cpp", "int main() {}");
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path();
+        let (res, updates) =
+            collect_progress(
+                |mut pw| async move { import(src, dest, Some("contest"), &mut pw).await },
+            )
+            .await;
+        res.unwrap();
+        let warnings: Vec<_> = updates
+            .iter()
+            .filter_map(|update| match update {
+                ImportUpdate::Diagnostic(d) if d.severity == Severity::Warning => {
+                    Some((d.code, d.location.as_ref().map(|loc| loc.line)))
+                }
+                _ => None,
+            })
+            .collect();
+        // only B does not use correct answers
+        assert_eq!(warnings, [(DiagnosticCode::UnsupportedFeature, Some(13))]);
+
+        let read_problem = |name: &str| -> RawProblem {
+            let manifest = std::fs::read_to_string(dest.join(name).join("problem.toml")).unwrap();
+            toml::from_str(&manifest).unwrap()
+        };
+        let a = read_problem("A");
+        assert_eq!(a.tests.len(), 1);
+        assert_eq!(a.tests[0].map, "1..2");
+        assert_eq!(a.tests[0].files.as_deref(), Some("%03d.in"));
+        assert_eq!(a.tests[0].answers.as_deref(), Some("%03d.out"));
+        assert!(dest.join("A/tests/002.out").exists());
+        let b = read_problem("B");
+        assert_eq!(b.tests[0].map, "1..1");
+        assert_eq!(b.tests[0].answers, None);
+        // answers of B are generated by reference solution
+        assert_eq!(a.primary_solution, None);
+        assert_eq!(b.primary_solution.as_deref(), Some("main"));
+        assert!(dest.join("B/solutions/main/main.cpp").exists());
+    }
+
+    #[tokio::test]
+    async fn missing_reference_solution() {
+        let src = tempfile::tempdir().unwrap();
+        let src = src.path();
+        write(
+            src,
+            "conf/serve.cfg",
+            r#"[problem]
+short_name = "A"
+use_corr = 0
+standard_checker = "cmp_int"
+"#,
+        );
+        write(src, "tests/A/001.dat", "1\n");
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path();
+        let (res, updates) =
+            collect_progress(
+                |mut pw| async move { import(src, dest, Some("contest"), &mut pw).await },
+            )
+            .await;
+        // answers can not be generated, so package can not be built
+        assert!(res.is_err());
+        let errors: Vec<_> = updates
+            .iter()
+            .filter_map(|update| match update {
+                ImportUpdate::Diagnostic(d) if d.severity == Severity::Error => {
+                    Some((d.code, d.location.as_ref().map(|loc| loc.line)))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors, [(DiagnosticCode::MissingValue, Some(1))]);
+    }
+}
@@ -0,0 +1,811 @@
+//! Importer for Kattis problem package format.
+//! Package contains `problem.yaml`, tests in `data/sample` and `data/secret`,
+//! output validators and submissions grouped by expected verdict.
+//! Input validators are not imported, because pps packages can not describe them.
+use super::{Diagnostic, DiagnosticCode, ImportUpdate, PropertyName, Severity, SourceLocation};
+use crate::{
+    manifest::{RawProblem, RawTestsSpec},
+    operation::ProgressWriter,
+};
+use anyhow::Context as _;
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+};
+
+/// Kattis test group, i.e. `data/sample` or directory inside `data/secret`
+struct KattisGroup {
+    name: String,
+    /// Score for each test, used unless `range` is given
+    accept_score: f64,
+    /// Maximal group score
+    max_score: Option<f64>,
+    /// Whether group should stop after first failed test
+    break_on_reject: bool,
+    tests_count: usize,
+}
+
+/// Returns sorted directory entries
+fn list_dir(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(path).with_context(|| format!("read {}", path.display()))? {
+        entries.push(entry?.path());
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Converts submission name to pps solution name
+fn solution_name(verdict: &str, stem: &str) -> String {
+    format!("{}-{}", verdict, stem)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn is_cpp_source(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("cpp") | Some("cc") | Some("cxx")
+    )
+}
+
+pub(crate) struct Importer<'a> {
+    pub(crate) src: &'a Path,
+    pub(crate) dest: &'a Path,
+    pub(crate) pw: &'a mut ProgressWriter<ImportUpdate>,
+    pub(crate) problem_cfg: RawProblem,
+    /// Parsed `problem.yaml`
+    config: serde_yaml::Value,
+    limits: pom::Limits,
+    groups: Vec<KattisGroup>,
+    /// All reported errors. If not empty, import fails.
+    errors: Vec<Diagnostic>,
+}
+
+impl<'a> Importer<'a> {
+    pub(crate) fn new(
+        src: &'a Path,
+        dest: &'a Path,
+        name: String,
+        pw: &'a mut ProgressWriter<ImportUpdate>,
+    ) -> Importer<'a> {
+        Importer {
+            src,
+            dest,
+            pw,
+            problem_cfg: RawProblem {
+                name,
+                ..RawProblem::default()
+            },
+            config: serde_yaml::Value::Null,
+            limits: pom::Limits::default(),
+            groups: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    async fn report(&mut self, diagnostic: Diagnostic) {
+        super::report(self.pw, &mut self.errors, diagnostic).await;
+    }
+
+    /// Returns `problem.yaml` value by key
+    fn config_value(&self, key: &str) -> Option<&serde_yaml::Value> {
+        self.config.get(key)
+    }
+
+    fn config_str(&self, key: &str) -> Option<&str> {
+        self.config_value(key).and_then(|v| v.as_str())
+    }
+
+    async fn read_config(&mut self) -> anyhow::Result<()> {
+        let data = tokio::fs::read_to_string(self.src.join("problem.yaml"))
+            .await
+            .context("failed read problem.yaml")?;
+        match serde_yaml::from_str::<serde_yaml::Value>(&data) {
+            Ok(serde_yaml::Value::Null) => (),
+            Ok(config) => self.config = config,
+            Err(err) => {
+                let mut diagnostic =
+                    Diagnostic::error(DiagnosticCode::SyntaxError, err.to_string());
+                if let Some(loc) = err.location() {
+                    diagnostic = diagnostic.at(SourceLocation {
+                        file: "problem.yaml".to_string(),
+                        line: loc.line() as u32,
+                        column: loc.column() as u32,
+                    });
+                }
+                self.report(diagnostic).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn import_names(&mut self) {
+        let name = self.problem_cfg.name.clone();
+        // `name` is either string or map from language to string
+        let title = match self.config_value("name") {
+            Some(serde_yaml::Value::String(title)) => Some(title.clone()),
+            Some(serde_yaml::Value::Mapping(titles)) => titles
+                .get(&serde_yaml::Value::from("en"))
+                .or_else(|| titles.iter().next().map(|(_, v)| v))
+                .and_then(|v| v.as_str())
+                .map(ToString::to_string),
+            _ => None,
+        };
+        let title = title.unwrap_or_else(|| name.clone());
+        self.pw
+            .send(ImportUpdate::Property {
+                property_name: PropertyName::ProblemTitle,
+                property_value: title.clone(),
+            })
+            .await;
+        self.problem_cfg.title = title;
+    }
+
+    async fn import_limits(&mut self) -> anyhow::Result<()> {
+        let limits = self.config_value("limits").cloned().unwrap_or_default();
+        // Kattis derives time limit from submissions running time,
+        // so only explicitly stored limits can be imported
+        let time_limit_sec = match tokio::fs::read_to_string(self.src.join(".timelimit")).await {
+            Ok(data) => data.trim().parse::<f64>().ok(),
+            Err(_) => limits.get("time_limit").and_then(|v| v.as_f64()),
+        };
+        match time_limit_sec {
+            Some(sec) if sec > 0.0 => {
+                let tl = (sec * 1000.0).round() as u64;
+                self.limits.time.replace(tl);
+                self.pw
+                    .send(ImportUpdate::Property {
+                        property_name: PropertyName::TimeLimit,
+                        property_value: tl.to_string(),
+                    })
+                    .await;
+            }
+            _ => {
+                let message = "time limit is not specified, default is used";
+                self.report(Diagnostic::warning(DiagnosticCode::MissingValue, message))
+                    .await;
+            }
+        }
+        if let Some(memory) = limits.get("memory") {
+            match memory.as_u64() {
+                Some(mib) => {
+                    let ml = mib * (1 << 20);
+                    self.limits.memory.replace(ml);
+                    self.pw
+                        .send(ImportUpdate::Property {
+                            property_name: PropertyName::MemoryLimit,
+                            property_value: ml.to_string(),
+                        })
+                        .await;
+                }
+                None => {
+                    let message = format!("problem.yaml: invalid memory limit: {:?}", memory);
+                    self.report(Diagnostic::error(DiagnosticCode::InvalidValue, message))
+                        .await;
+                }
+            }
+        }
+        self.problem_cfg.limits = self.limits;
+        Ok(())
+    }
+
+    /// Reads group settings from `testdata.yaml`
+    async fn read_group(&mut self, dir: &Path, name: String) -> KattisGroup {
+        let mut group = KattisGroup {
+            name,
+            accept_score: 1.0,
+            max_score: None,
+            break_on_reject: true,
+            tests_count: 0,
+        };
+        let data = match tokio::fs::read_to_string(dir.join("testdata.yaml")).await {
+            Ok(data) => data,
+            Err(_) => return group,
+        };
+        let testdata = match serde_yaml::from_str::<serde_yaml::Value>(&data) {
+            Ok(testdata) => testdata,
+            Err(err) => {
+                let message = format!("group {}: testdata.yaml: {}", group.name, err);
+                self.report(Diagnostic::error(DiagnosticCode::SyntaxError, message))
+                    .await;
+                return group;
+            }
+        };
+        if let Some(score) = testdata.get("accept_score").and_then(|v| v.as_f64()) {
+            group.accept_score = score;
+        }
+        if let Some(range) = testdata.get("range").and_then(|v| v.as_str()) {
+            group.max_score = range
+                .split_whitespace()
+                .nth(1)
+                .and_then(|max| max.parse::<f64>().ok());
+        }
+        if testdata.get("on_reject").and_then(|v| v.as_str()) == Some("continue") {
+            group.break_on_reject = false;
+        }
+        if testdata.get("grading").and_then(|v| v.as_str()) == Some("custom") {
+            let message = format!("group {}: custom graders are not supported", group.name);
+            self.report(Diagnostic::warning(
+                DiagnosticCode::UnsupportedFeature,
+                message,
+            ))
+            .await;
+        }
+        group
+    }
+
+    /// Imports tests of directory `dir` into group `group_name`.
+    /// Subdirectories are imported as separate groups if `nested_groups` is set.
+    fn import_test_dir<'b>(
+        &'b mut self,
+        dir: PathBuf,
+        group_name: String,
+        nested_groups: bool,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'b>> {
+        Box::pin(async move {
+            // without nested groups, subdirectories belong to the parent group
+            let group_idx = match self.groups.iter().position(|g| g.name == group_name) {
+                Some(idx) => idx,
+                None => {
+                    let group = self.read_group(&dir, group_name.clone()).await;
+                    self.groups.push(group);
+                    self.groups.len() - 1
+                }
+            };
+            let entries = list_dir(&dir)?;
+            for input in &entries {
+                if input.extension().and_then(|ext| ext.to_str()) != Some("in") {
+                    continue;
+                }
+                let test_id = self.problem_cfg.tests.len() + 1;
+                let answer = input.with_extension("ans");
+                if !answer.is_file() {
+                    let message = format!(
+                        "{}: answer file not found",
+                        input.strip_prefix(self.src).unwrap_or(input).display()
+                    );
+                    self.report(Diagnostic::error(DiagnosticCode::MissingFile, message))
+                        .await;
+                    continue;
+                }
+                let tests_dir = self.dest.join("tests");
+                tokio::fs::copy(input, tests_dir.join(format!("{:0>2}.txt", test_id)))
+                    .await
+                    .with_context(|| format!("failed to copy {}", input.display()))?;
+                tokio::fs::copy(&answer, tests_dir.join(format!("{:0>2}.a.txt", test_id)))
+                    .await
+                    .with_context(|| format!("failed to copy {}", answer.display()))?;
+                self.problem_cfg.tests.push(RawTestsSpec {
+                    map: test_id.to_string(),
+                    testgen: None,
+                    files: Some("%02d.txt".to_string()),
+                    answers: Some("%02d.a.txt".to_string()),
+                    limits: self.limits,
+                    group: group_name.clone(),
+                    tags: Vec::new(),
+                });
+                self.groups[group_idx].tests_count += 1;
+            }
+            for subdir in entries.into_iter().filter(|path| path.is_dir()) {
+                let subgroup_name = if nested_groups {
+                    format!("{}-{}", group_name, file_name(&subdir))
+                } else {
+                    group_name.clone()
+                };
+                self.import_test_dir(subdir, subgroup_name, nested_groups)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn is_scoring(&self) -> bool {
+        self.config_str("type") == Some("scoring")
+    }
+
+    async fn import_tests(&mut self) -> anyhow::Result<()> {
+        self.pw.send(ImportUpdate::ImportTests).await;
+        let scoring = self.is_scoring();
+        let sample_dir = self.src.join("data/sample");
+        if sample_dir.is_dir() {
+            self.import_test_dir(sample_dir, "sample".to_string(), false)
+                .await?;
+        }
+        let secret_dir = self.src.join("data/secret");
+        if secret_dir.is_dir() {
+            self.import_test_dir(secret_dir, "secret".to_string(), scoring)
+                .await?;
+        } else {
+            let message = "data/secret not found";
+            self.report(Diagnostic::error(DiagnosticCode::MissingFile, message))
+                .await;
+        }
+        self.groups.retain(|group| group.tests_count != 0);
+        self.pw
+            .send(ImportUpdate::ImportTestsDone {
+                count: self.problem_cfg.tests.len(),
+            })
+            .await;
+        Ok(())
+    }
+
+    /// Generates svaluer config from test groups
+    async fn import_valuer_config(&mut self) -> anyhow::Result<()> {
+        self.pw.send(ImportUpdate::GenerateValuerConfig).await;
+        let scoring = self.is_scoring();
+        let has_samples = self.groups.iter().any(|group| group.name == "sample");
+        let mut valuer_groups = Vec::new();
+        let mut warnings = Vec::new();
+        for group in &self.groups {
+            let is_sample = group.name == "sample";
+            let score = if is_sample {
+                0.0
+            } else if scoring {
+                group
+                    .max_score
+                    .unwrap_or(group.accept_score * group.tests_count as f64)
+            } else {
+                100.0
+            };
+            if score.fract() != 0.0 {
+                warnings.push(format!(
+                    "group {}: fractional score {} is rounded",
+                    group.name, score
+                ));
+            }
+            // scores of accepted tests are summed up
+            let test_score = if scoring
+                && !is_sample
+                && group.max_score.is_none()
+                && group.accept_score.fract() == 0.0
+            {
+                Some(group.accept_score as u32)
+            } else {
+                None
+            };
+            let mut deps = Vec::new();
+            // in pass-fail problems all tests must pass, including samples
+            if !scoring && !is_sample && has_samples {
+                deps.push(svaluer::cfg::GroupRef::ByName("sample".to_string()));
+            }
+            valuer_groups.push(svaluer::cfg::Group {
+                name: group.name.clone(),
+                feedback: if is_sample {
+                    svaluer::cfg::FeedbackKind::Full
+                } else {
+                    svaluer::cfg::FeedbackKind::Brief
+                },
+                tests_tag: None,
+                run_to_first_failure: group.break_on_reject,
+                score: if test_score.is_some() {
+                    0
+                } else {
+                    score.round() as u32
+                },
+                scoring: svaluer::cfg::ScoringPolicy::Complete,
+                deps,
+                test_score,
+                pass_if_count: None,
+                skip: false,
+                zero_if_failed: false,
+                fraction_policy: None,
+            });
+        }
+        for message in warnings {
+            self.report(Diagnostic::warning(
+                DiagnosticCode::UnsupportedFeature,
+                message,
+            ))
+            .await;
+        }
+        let config = svaluer::Config {
+            groups: valuer_groups,
+            ..Default::default()
+        };
+        tokio::fs::write(
+            self.dest.join("valuer.yaml"),
+            serde_yaml::to_string(&config)?,
+        )
+        .await?;
+        self.problem_cfg.valuer_cfg = Some("valuer.yaml".to_string());
+        Ok(())
+    }
+
+    async fn import_default_validator(&mut self, flags: &[&str]) {
+        let mut args = Vec::new();
+        if !flags.contains(&"case_sensitive") {
+            args.push("--ignore-case".to_string());
+        }
+        let flag_value = |name: &str| {
+            let pos = flags.iter().position(|flag| *flag == name)?;
+            flags.get(pos + 1).and_then(|v| v.parse::<f64>().ok())
+        };
+        let epsilon = match flag_value("float_tolerance") {
+            Some(eps) => Some(eps),
+            None => {
+                let abs = flag_value("float_absolute_tolerance");
+                let rel = flag_value("float_relative_tolerance");
+                if abs.is_some() || rel.is_some() {
+                    let message =
+                        "absolute and relative float tolerances are merged into single epsilon";
+                    self.report(Diagnostic::warning(
+                        DiagnosticCode::UnsupportedFeature,
+                        message,
+                    ))
+                    .await;
+                }
+                match (abs, rel) {
+                    (Some(abs), Some(rel)) => Some(abs.min(rel)),
+                    (abs, rel) => abs.or(rel),
+                }
+            }
+        };
+        if let Some(eps) = epsilon {
+            args.push("--epsilon".to_string());
+            args.push(eps.to_string());
+        }
+        if flags.contains(&"space_change_sensitive") {
+            let message = "space_change_sensitive is not supported, whitespace is ignored";
+            self.report(Diagnostic::warning(
+                DiagnosticCode::UnsupportedFeature,
+                message,
+            ))
+            .await;
+        }
+        let builtin_name = "cmp-tokens";
+        self.pw
+            .send(ImportUpdate::BuiltinChecker(builtin_name.to_string()))
+            .await;
+        self.problem_cfg.builtin_check = Some(crate::manifest::BuiltinCheck {
+            name: builtin_name.to_string(),
+        });
+        self.problem_cfg.check_options = Some(crate::manifest::CheckOptions { args });
+    }
+
+    async fn import_custom_validator(&mut self, flags: &[&str]) -> anyhow::Result<()> {
+        let validators_dir = self.src.join("output_validators");
+        if !validators_dir.is_dir() {
+            let message = "validation is custom, but output_validators not found";
+            self.report(Diagnostic::error(DiagnosticCode::MissingFile, message))
+                .await;
+            return Ok(());
+        }
+        // validator is either the only subdirectory, or files directly in output_validators
+        let subdirs = list_dir(&validators_dir)?
+            .into_iter()
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        let validator_dir = match subdirs.as_slice() {
+            [] => validators_dir,
+            [dir] => dir.clone(),
+            _ => {
+                let message = "output_validators contains more than one validator";
+                self.report(Diagnostic::error(
+                    DiagnosticCode::UnsupportedFeature,
+                    message,
+                ))
+                .await;
+                return Ok(());
+            }
+        };
+        let files = list_dir(&validator_dir)?
+            .into_iter()
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        if !files.iter().any(|path| is_cpp_source(path)) {
+            let message = format!(
+                "{}: only C++ output validators can be imported",
+                validator_dir
+                    .strip_prefix(self.src)
+                    .unwrap_or(&validator_dir)
+                    .display()
+            );
+            self.report(Diagnostic::error(
+                DiagnosticCode::UnsupportedFeature,
+                message,
+            ))
+            .await;
+            return Ok(());
+        }
+        let module_dir = self.dest.join("modules/checker");
+        tokio::fs::create_dir(&module_dir)
+            .await
+            .context("failed to create checker dir")?;
+        for file in &files {
+            tokio::fs::copy(file, module_dir.join(file_name(file)))
+                .await
+                .with_context(|| format!("failed to copy {}", file.display()))?;
+        }
+        tokio::fs::write(
+            module_dir.join("CMakeLists.txt"),
+            super::template::get_output_validator_cmakefile(),
+        )
+        .await
+        .context("write checker's CMakeLists.txt")?;
+        let builtin_name = "kattis-compat";
+        self.pw
+            .send(ImportUpdate::BuiltinChecker(builtin_name.to_string()))
+            .await;
+        self.problem_cfg.builtin_check = Some(crate::manifest::BuiltinCheck {
+            name: builtin_name.to_string(),
+        });
+        let mut args = vec!["assets/module-checker/bin".to_string()];
+        args.extend(flags.iter().map(ToString::to_string));
+        self.problem_cfg.check_options = Some(crate::manifest::CheckOptions { args });
+        Ok(())
+    }
+
+    async fn import_checker(&mut self) -> anyhow::Result<()> {
+        self.pw.send(ImportUpdate::ImportChecker).await;
+        let validation = self
+            .config_str("validation")
+            .unwrap_or("default")
+            .to_string();
+        let flags = self.config_str("validator_flags").unwrap_or("").to_string();
+        let flags = flags.split_whitespace().collect::<Vec<_>>();
+        let mut modes = validation.split_whitespace();
+        match modes.next() {
+            Some("default") => self.import_default_validator(&flags).await,
+            Some("custom") => {
+                for mode in modes {
+                    let (severity, message) = match mode {
+                        "interactive" => (
+                            Severity::Error,
+                            "interactive problems are not supported".to_string(),
+                        ),
+                        "score" => (
+                            Severity::Warning,
+                            "scores reported by output validator are ignored".to_string(),
+                        ),
+                        other => (
+                            Severity::Warning,
+                            format!("unknown validation mode {}", other),
+                        ),
+                    };
+                    self.report(Diagnostic {
+                        severity,
+                        code: DiagnosticCode::UnsupportedFeature,
+                        message,
+                        location: None,
+                    })
+                    .await;
+                }
+                self.import_custom_validator(&flags).await?;
+            }
+            _ => {
+                let message = format!("problem.yaml: invalid validation: {}", validation);
+                self.report(Diagnostic::error(DiagnosticCode::InvalidValue, message))
+                    .await;
+            }
+        }
+        for validators in &["input_validators", "input_format_validators"] {
+            if self.src.join(validators).is_dir() {
+                let message = format!(
+                    "{} are not imported: pps does not support input validation",
+                    validators
+                );
+                self.report(Diagnostic::warning(
+                    DiagnosticCode::UnsupportedFeature,
+                    message,
+                ))
+                .await;
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    async fn import_solutions(&mut self) -> anyhow::Result<()> {
+        let submissions_dir = self.src.join("submissions");
+        if !submissions_dir.is_dir() {
+            return Ok(());
+        }
+        self.pw.send(ImportUpdate::ImportSolutions).await;
+        for verdict_dir in list_dir(&submissions_dir)? {
+            if !verdict_dir.is_dir() {
+                continue;
+            }
+            let verdict = file_name(&verdict_dir);
+            for submission in list_dir(&verdict_dir)? {
+                if !submission.is_file() || !is_cpp_source(&submission) {
+                    let message = format!(
+                        "skipping submission {}: only single-file C++ submissions can be imported",
+                        submission
+                            .strip_prefix(self.src)
+                            .unwrap_or(&submission)
+                            .display()
+                    );
+                    self.report(Diagnostic::warning(
+                        DiagnosticCode::UnsupportedFeature,
+                        message,
+                    ))
+                    .await;
+                    continue;
+                }
+                let stem = submission
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let name = solution_name(&verdict, &stem);
+                self.pw
+                    .send(ImportUpdate::ImportSolution(name.clone()))
+                    .await;
+                let dir = self.dest.join("solutions").join(&name);
+                tokio::fs::create_dir_all(&dir)
+                    .await
+                    .with_context(|| format!("create solution dir {}", name))?;
+                tokio::fs::copy(&submission, dir.join("main.cpp"))
+                    .await
+                    .with_context(|| format!("failed to copy {}", submission.display()))?;
+                tokio::fs::write(dir.join("CMakeLists.txt"), include_str!("./solution.cmake"))
+                    .await
+                    .context("write CMakeLists.txt for solution")?;
+                if verdict == "accepted" && self.problem_cfg.primary_solution.is_none() {
+                    self.problem_cfg.primary_solution = Some(name);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn run(&mut self) -> anyhow::Result<()> {
+        super::init_dirs(self.dest)?;
+        self.problem_cfg.valuer = "icpc".to_string();
+        self.problem_cfg.check_type = "builtin".to_string();
+        self.problem_cfg.random_seed = Some(super::generate_random_seed()?);
+        self.read_config().await?;
+        self.import_names().await;
+        self.import_limits().await?;
+        self.import_tests().await?;
+        self.import_checker().await?;
+        self.import_solutions().await?;
+        self.import_valuer_config().await?;
+        super::check_errors(&self.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::write;
+
+    fn add_test(root: &Path, stem: &str) {
+        write(root, &format!("{}.in", stem), "1 2\n");
+        write(root, &format!("{}.ans", stem), "3\n");
+    }
+
+    /// Imports package `src` and returns manifest, valuer config and errors
+    async fn import(src: &Path) -> (RawProblem, svaluer::Config, Vec<Diagnostic>) {
+        let dest = tempfile::tempdir().unwrap();
+        let mut pw = crate::test_util::discard_progress();
+        let mut importer = Importer::new(src, dest.path(), "aplusb".to_string(), &mut pw);
+        importer.run().await.ok();
+        let valuer_cfg = std::fs::read_to_string(dest.path().join("valuer.yaml")).unwrap();
+        let valuer_cfg = serde_yaml::from_str(&valuer_cfg).unwrap();
+        (importer.problem_cfg, valuer_cfg, importer.errors)
+    }
+
+    #[tokio::test]
+    async fn scoring_problem() {
+        let src = tempfile::tempdir().unwrap();
+        let src = src.path();
+        write(
+            src,
+            "problem.yaml",
+            "name:\n  en: A plus B\ntype: scoring\nlimits:\n  memory: 256\n",
+        );
+        write(src, ".timelimit", "1.5\n");
+        add_test(src, "data/sample/1");
+        add_test(src, "data/secret/group1/1");
+        add_test(src, "data/secret/group1/2");
+        write(
+            src,
+            "data/secret/group1/testdata.yaml",
+            "accept_score: 10\n",
+        );
+        add_test(src, "data/secret/group2/1");
+        write(
+            src,
+            "data/secret/group2/testdata.yaml",
+            "range: 0 70\non_reject: continue\n",
+        );
+        write(src, "submissions/accepted/Main.cpp", "int main() {}");
+        write(src, "submissions/wrong_answer/wa.cpp", "int main() {}");
+
+        let (problem, config, errors) = import(src).await;
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(problem.title, "A plus B");
+        assert_eq!(problem.limits.time, Some(1500));
+        assert_eq!(problem.limits.memory, Some(256 << 20));
+        let groups: Vec<_> = problem.tests.iter().map(|t| t.group.as_str()).collect();
+        assert_eq!(
+            groups,
+            ["sample", "secret-group1", "secret-group1", "secret-group2"]
+        );
+        assert_eq!(problem.tests[3].map, "4");
+        assert_eq!(problem.primary_solution.as_deref(), Some("accepted-main"));
+        assert_eq!(problem.builtin_check.unwrap().name, "cmp-tokens");
+
+        // `secret` itself has no tests, so it is not a group
+        let groups: Vec<_> = config
+            .groups
+            .iter()
+            .map(|g| {
+                (
+                    g.name.as_str(),
+                    g.score,
+                    g.test_score,
+                    g.run_to_first_failure,
+                )
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("sample", 0, None, true),
+                ("secret-group1", 0, Some(10), true),
+                ("secret-group2", 70, None, false),
+            ]
+        );
+        assert!(matches!(
+            config.groups[0].feedback,
+            svaluer::cfg::FeedbackKind::Full
+        ));
+    }
+
+    #[tokio::test]
+    async fn pass_fail_problem() {
+        let src = tempfile::tempdir().unwrap();
+        let src = src.path();
+        write(
+            src,
+            "problem.yaml",
+            "validator_flags: float_tolerance 1e-6 case_sensitive\n",
+        );
+        add_test(src, "data/sample/1");
+        add_test(src, "data/secret/1");
+        add_test(src, "data/secret/nested/2");
+        write(src, "data/secret/3.in", "no answer");
+
+        let (problem, config, errors) = import(src).await;
+        // test without answer is reported
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, DiagnosticCode::MissingFile);
+        assert_eq!(problem.title, "aplusb");
+        // without groups, nested directories belong to parent group
+        let groups: Vec<_> = problem.tests.iter().map(|t| t.group.as_str()).collect();
+        assert_eq!(groups, ["sample", "secret", "secret"]);
+        assert_eq!(
+            problem.check_options.unwrap().args,
+            ["--epsilon", "0.000001"]
+        );
+        assert_eq!(config.groups.len(), 2);
+        assert_eq!(config.groups[1].score, 100);
+        assert!(matches!(
+            &config.groups[1].deps[..],
+            [svaluer::cfg::GroupRef::ByName(dep)] if dep == "sample"
+        ));
+    }
+
+    #[test]
+    fn solution_names() {
+        assert_eq!(solution_name("accepted", "Main_v2"), "accepted-main-v2");
+        assert!(is_cpp_source(Path::new("a/b.cc")));
+        assert!(!is_cpp_source(Path::new("a/b.py")));
+    }
+}
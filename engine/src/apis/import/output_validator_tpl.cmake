@@ -0,0 +1,5 @@
+project(Checker)
+cmake_minimum_required(VERSION 3.12)
+file(GLOB SRC_FILES *.cpp *.cc)
+add_executable(Out ${SRC_FILES})
+target_compile_options(Out PUBLIC -O2)
\ No newline at end of file
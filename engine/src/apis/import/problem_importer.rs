@@ -1,7 +1,7 @@
-use crate::apis::import::{ImportUpdate, PropertyName};
+use crate::apis::import::{Diagnostic, DiagnosticCode, ImportUpdate, PropertyName, SourceLocation};
 use crate::operation::ProgressWriter;
 use anyhow::Context as _;
-use std::{collections::HashSet, future::Future, path::Path, pin::Pin};
+use std::{collections::HashSet, future::Future, path::Path, pin::Pin, str::FromStr};
 
 pub(crate) struct Importer<'a> {
     pub(crate) src: &'a Path,
@@ -11,8 +11,95 @@ pub(crate) struct Importer<'a> {
     pub(crate) doc: roxmltree::Node<'a, 'a>,
     pub(crate) limits: pom::Limits,
     pub(crate) pw: &'a mut ProgressWriter<ImportUpdate>,
+    /// svaluer groups derived from Polygon testsets
+    pub(crate) valuer_groups: Vec<svaluer::cfg::Group>,
+    /// Set if `valuer_groups` differ from default valuer config
+    pub(crate) custom_valuer_groups: bool,
+    /// Import generated tests and answers from package instead of generators
+    pub(crate) static_tests: bool,
+    /// All reported errors. If not empty, import fails.
+    pub(crate) errors: Vec<Diagnostic>,
 }
 
+/// Test as seen in a Polygon testset
+struct PolygonTest {
+    group: Option<String>,
+    points: f64,
+}
+
+/// Group as defined in `<groups>` section of a Polygon testset
+struct PolygonGroup {
+    name: String,
+    /// Group score, if specified explicitly
+    points: Option<f64>,
+    points_policy: String,
+    feedback_policy: String,
+    /// Names of required groups
+    deps: Vec<String>,
+}
+
+struct Testset {
+    name: String,
+    /// Main testset gives points, all others are only imported
+    is_main: bool,
+    /// Printf-style pattern of input files
+    input_pattern: String,
+    /// Printf-style pattern of answer files
+    answer_pattern: String,
+    tests: Vec<PolygonTest>,
+    groups: Vec<PolygonGroup>,
+}
+
+impl Testset {
+    /// Returns tag for tests of Polygon group `group` in this testset
+    fn group_tag(&self, group: Option<&str>) -> String {
+        let tag = match group {
+            Some(g) if !g.is_empty() => format!("g{}", g),
+            _ => "default".to_string(),
+        };
+        if self.is_main {
+            tag
+        } else {
+            format!("{}-{}", self.name, tag)
+        }
+    }
+}
+
+/// Polygon stores points as decimals (e.g. `10.0`)
+fn parse_points(s: &str) -> Result<f64, String> {
+    let points = s.parse::<f64>().map_err(|err| err.to_string())?;
+    if !points.is_finite() || points < 0.0 {
+        return Err(format!("points must be non-negative, got {}", s));
+    }
+    Ok(points)
+}
+
+fn xml_location(node: roxmltree::Node) -> SourceLocation {
+    let pos = node.document().text_pos_at(node.range().start);
+    SourceLocation {
+        file: "problem.xml".to_string(),
+        line: pos.row,
+        column: pos.col,
+    }
+}
+
+/// Polygon standard checkers which have builtin equivalent.
+/// Each entry is (Polygon name, builtin checker name, builtin checker args).
+/// Other standard checkers are imported as usual testlib checkers.
+pub(crate) const STANDARD_CHECKERS: &[(&str, &str, &[&str])] = &[
+    ("std::wcmp.cpp", "cmp-tokens", &[]),
+    ("std::ncmp.cpp", "cmp-tokens", &[]),
+    ("std::icmp.cpp", "cmp-tokens", &[]),
+    ("std::hcmp.cpp", "cmp-tokens", &[]),
+    ("std::rcmp.cpp", "cmp-tokens", &["--epsilon", "1.5e-6"]),
+    ("std::rcmp4.cpp", "cmp-tokens", &["--epsilon", "1e-4"]),
+    ("std::rcmp6.cpp", "cmp-tokens", &["--epsilon", "1e-6"]),
+    ("std::rcmp9.cpp", "cmp-tokens", &["--epsilon", "1e-9"]),
+    ("std::dcmp.cpp", "cmp-tokens", &["--epsilon", "1e-6"]),
+    ("std::yesno.cpp", "cmp-tokens", &["--ignore-case"]),
+    ("std::nyesno.cpp", "cmp-tokens", &["--ignore-case"]),
+];
+
 enum FileCategory {
     Validator,
     Checker,
@@ -38,86 +125,457 @@ impl FileCategory {
 }
 
 impl<'a> Importer<'a> {
+    async fn report(&mut self, diagnostic: Diagnostic) {
+        super::report(self.pw, &mut self.errors, diagnostic).await;
+    }
+
+    async fn report_at(&mut self, node: roxmltree::Node<'_, '_>, diagnostic: Diagnostic) {
+        self.report(diagnostic.at(xml_location(node))).await;
+    }
+
+    /// Returns attribute value, reporting an error if it is missing
+    async fn required_attribute<'n>(
+        &mut self,
+        node: roxmltree::Node<'n, '_>,
+        attribute: &str,
+    ) -> Option<&'n str> {
+        let value = node.attribute(attribute);
+        if value.is_none() {
+            let message = format!(
+                "<{}> does not have {} attribute",
+                node.tag_name().name(),
+                attribute
+            );
+            self.report_at(
+                node,
+                Diagnostic::error(DiagnosticCode::MissingValue, message),
+            )
+            .await;
+        }
+        value
+    }
+
+    /// Parses element text, reporting an error if it is missing or malformed
+    async fn parse_text<T: FromStr>(&mut self, node: roxmltree::Node<'_, '_>) -> Option<T>
+    where
+        <T as FromStr>::Err: std::fmt::Display,
+    {
+        let text = match node.text() {
+            Some(text) => text.trim(),
+            None => {
+                let message = format!("<{}> does not contain value", node.tag_name().name());
+                self.report_at(
+                    node,
+                    Diagnostic::error(DiagnosticCode::MissingValue, message),
+                )
+                .await;
+                return None;
+            }
+        };
+        let res = text.parse::<T>().map_err(|err| err.to_string());
+        match res {
+            Ok(val) => Some(val),
+            Err(err) => {
+                let message = format!(
+                    "parsing <{}> value {} failed: {}",
+                    node.tag_name().name(),
+                    text,
+                    err
+                );
+                self.report_at(
+                    node,
+                    Diagnostic::error(DiagnosticCode::InvalidValue, message),
+                )
+                .await;
+                None
+            }
+        }
+    }
+
+    /// Parses `points` attribute, reporting an error if it is malformed
+    async fn parse_points_attribute(&mut self, node: roxmltree::Node<'_, '_>) -> Option<f64> {
+        let res = parse_points(node.attribute("points")?);
+        match res {
+            Ok(points) => Some(points),
+            Err(err) => {
+                let message = format!("invalid points: {}", err);
+                self.report_at(
+                    node,
+                    Diagnostic::error(DiagnosticCode::InvalidValue, message),
+                )
+                .await;
+                None
+            }
+        }
+    }
+
     // <problem><judging> is most important section for us: it contains information
     // about tests
     async fn process_judging_section(
         &mut self,
         node_judging: roxmltree::Node<'_, '_>,
     ) -> anyhow::Result<()> {
-        let node_testset = node_judging
-            .first_element_child()
-            .context("<judging> element does not have a <testset> child")?;
-        assert_eq!(node_testset.tag_name().name(), "testset");
+        let testsets = node_judging
+            .children()
+            .filter(|child| child.is_element())
+            .collect::<Vec<_>>();
+        if testsets.is_empty() {
+            let message = "<judging> element does not have a <testset> child";
+            self.report_at(
+                node_judging,
+                Diagnostic::error(DiagnosticCode::MissingValue, message),
+            )
+            .await;
+        }
+        // Polygon always calls main testset `tests`. Other testsets (e.g. pretests)
+        // are imported too, but they do not give any points.
+        let main_testset_pos = testsets
+            .iter()
+            .position(|node| node.attribute("name") == Some("tests"))
+            .unwrap_or(0);
+        let mut secondary_limits = Vec::new();
+        for (i, node_testset) in testsets.into_iter().enumerate() {
+            if node_testset.tag_name().name() != "testset" {
+                let message = format!(
+                    "unexpected tag in <problem><judging>: {}",
+                    node_testset.tag_name().name()
+                );
+                self.report_at(
+                    node_testset,
+                    Diagnostic::warning(DiagnosticCode::UnexpectedElement, message),
+                )
+                .await;
+                continue;
+            }
+            let is_main = i == main_testset_pos;
+            let limits = self.process_testset(node_testset, is_main).await?;
+            if !is_main {
+                secondary_limits.push((node_testset, limits));
+            }
+        }
+        // only limits of main testset are imported
+        for (node_testset, limits) in secondary_limits {
+            let differs = |limit: Option<u64>, main_limit: Option<u64>| {
+                limit.is_some() && limit != main_limit
+            };
+            if differs(limits.time, self.limits.time) || differs(limits.memory, self.limits.memory)
+            {
+                let message = format!(
+                    "testset {}: limits differ from main testset and are ignored",
+                    node_testset.attribute("name").unwrap_or("tests")
+                );
+                self.report_at(
+                    node_testset,
+                    Diagnostic::warning(DiagnosticCode::UnsupportedFeature, message),
+                )
+                .await;
+            }
+        }
+        Ok(())
+    }
 
-        let mut test_pattern = None;
-        let mut ans_pattern = None;
+    /// Returns limits specified in the testset.
+    async fn process_testset(
+        &mut self,
+        node_testset: roxmltree::Node<'_, '_>,
+        is_main: bool,
+    ) -> anyhow::Result<pom::Limits> {
+        let testset_name = node_testset.attribute("name").unwrap_or("tests");
+        let mut testset = Testset {
+            name: testset_name.to_string(),
+            is_main,
+            input_pattern: format!("{}/%02d", testset_name),
+            answer_pattern: format!("{}/%02d.a", testset_name),
+            tests: Vec::new(),
+            groups: Vec::new(),
+        };
+        let mut limits = pom::Limits::default();
+        let mut test_count = None;
         for child in node_testset.children() {
             if !child.is_element() {
                 continue;
             }
             match child.tag_name().name() {
                 "time-limit" => {
-                    let tl = child
-                        .text()
-                        .context("<time-limit> does not contain value")?
-                        .parse::<u64>()
-                        .context("parsing <time-limit> failed")?;
-                    self.pw
-                        .send(ImportUpdate::Property {
-                            property_name: PropertyName::TimeLimit,
-                            property_value: tl.to_string(),
-                        })
-                        .await;
-                    self.limits.time.replace(tl);
+                    let tl = match self.parse_text::<u64>(child).await {
+                        Some(tl) => tl,
+                        None => continue,
+                    };
+                    if is_main {
+                        self.pw
+                            .send(ImportUpdate::Property {
+                                property_name: PropertyName::TimeLimit,
+                                property_value: tl.to_string(),
+                            })
+                            .await;
+                        self.limits.time.replace(tl);
+                    }
+                    limits.time.replace(tl);
                 }
                 "memory-limit" => {
-                    let ml = child
-                        .text()
-                        .context("<memory-limiy> does not contain value")?
-                        .parse::<u64>()
-                        .context("parsing <memory-limit> failed")?;
-                    self.pw
-                        .send(ImportUpdate::Property {
-                            property_name: PropertyName::MemoryLimit,
-                            property_value: ml.to_string(),
-                        })
-                        .await;
-                    self.limits.memory.replace(ml);
+                    let ml = match self.parse_text::<u64>(child).await {
+                        Some(ml) => ml,
+                        None => continue,
+                    };
+                    if is_main {
+                        self.pw
+                            .send(ImportUpdate::Property {
+                                property_name: PropertyName::MemoryLimit,
+                                property_value: ml.to_string(),
+                            })
+                            .await;
+                        self.limits.memory.replace(ml);
+                    }
+                    limits.memory.replace(ml);
                 }
                 "input-path-pattern" => {
-                    let pat = child.text().unwrap().to_string();
-                    self.pw
-                        .send(ImportUpdate::Property {
-                            property_name: PropertyName::InputPathPattern,
-                            property_value: pat.clone(),
-                        })
-                        .await;
-                    test_pattern.replace(pat);
+                    let pat = match self.parse_text::<String>(child).await {
+                        Some(pat) => pat,
+                        None => continue,
+                    };
+                    if is_main {
+                        self.pw
+                            .send(ImportUpdate::Property {
+                                property_name: PropertyName::InputPathPattern,
+                                property_value: pat.clone(),
+                            })
+                            .await;
+                    }
+                    testset.input_pattern = pat;
                 }
                 "answer-path-pattern" => {
-                    let pat = child.text().unwrap().to_string();
-                    self.pw
-                        .send(ImportUpdate::Property {
-                            property_name: PropertyName::OutputPathPattern,
-                            property_value: pat.clone(),
-                        })
-                        .await;
-                    ans_pattern.replace(pat);
+                    let pat = match self.parse_text::<String>(child).await {
+                        Some(pat) => pat,
+                        None => continue,
+                    };
+                    if is_main {
+                        self.pw
+                            .send(ImportUpdate::Property {
+                                property_name: PropertyName::OutputPathPattern,
+                                property_value: pat.clone(),
+                            })
+                            .await;
+                    }
+                    testset.answer_pattern = pat;
+                }
+                "test-count" => {
+                    if let Some(cnt) = self.parse_text::<usize>(child).await {
+                        test_count.replace((cnt, child));
+                    }
                 }
-                "test-count" => {}
                 "tests" => {
-                    self.process_tests(child).await?;
+                    self.process_tests(node_testset, child, &mut testset)
+                        .await?;
+                }
+                "groups" => {
+                    self.process_groups(child, &mut testset).await;
                 }
                 _ => {
                     let message = format!(
                         "unexpected tag in <problem><judging><testset>: {}",
                         child.tag_name().name()
                     );
-                    self.pw.send(ImportUpdate::Warning(message)).await;
+                    self.report_at(
+                        child,
+                        Diagnostic::warning(DiagnosticCode::UnexpectedElement, message),
+                    )
+                    .await;
                 }
             }
         }
-        Ok(())
+        if let Some((cnt, node)) = test_count {
+            if cnt != testset.tests.len() {
+                let message = format!(
+                    "testset {}: <test-count> is {}, but {} tests found",
+                    testset_name,
+                    cnt,
+                    testset.tests.len()
+                );
+                self.report_at(
+                    node,
+                    Diagnostic::warning(DiagnosticCode::Inconsistency, message),
+                )
+                .await;
+            }
+        }
+        if !is_main && !testset.tests.is_empty() {
+            let message = format!(
+                "testset {}: tests are imported as groups without score, they are run on every submission",
+                testset_name
+            );
+            self.report_at(
+                node_testset,
+                Diagnostic::warning(DiagnosticCode::UnsupportedFeature, message),
+            )
+            .await;
+        }
+        self.process_testset_groups(testset).await;
+        Ok(limits)
+    }
+
+    /// Parses `<groups>` section of a testset.
+    async fn process_groups(
+        &mut self,
+        node_groups: roxmltree::Node<'_, '_>,
+        testset: &mut Testset,
+    ) {
+        for node_group in node_groups.children() {
+            if !node_group.is_element() {
+                continue;
+            }
+            let name = match self.required_attribute(node_group, "name").await {
+                Some(name) => name,
+                None => continue,
+            };
+            let points = self.parse_points_attribute(node_group).await;
+            let mut deps = Vec::new();
+            for node_deps in node_group.children() {
+                if node_deps.tag_name().name() != "dependencies" {
+                    continue;
+                }
+                for node_dep in node_deps.children() {
+                    if !node_dep.is_element() {
+                        continue;
+                    }
+                    if let Some(dep) = self.required_attribute(node_dep, "group").await {
+                        deps.push(dep.to_string());
+                    }
+                }
+            }
+            testset.groups.push(PolygonGroup {
+                name: name.to_string(),
+                points,
+                points_policy: node_group
+                    .attribute("points-policy")
+                    .unwrap_or("complete-group")
+                    .to_string(),
+                feedback_policy: node_group
+                    .attribute("feedback-policy")
+                    .unwrap_or("icpc")
+                    .to_string(),
+                deps,
+            });
+        }
+    }
+
+    /// Converts groups of the processed testset into svaluer groups.
+    async fn process_testset_groups(&mut self, mut testset: Testset) {
+        let has_groups = testset.tests.iter().any(|t| t.group.is_some());
+        let total_points: f64 = testset.tests.iter().map(|t| t.points).sum();
+        if testset.is_main && !has_groups && testset.groups.is_empty() && total_points == 0.0 {
+            // nothing Polygon-specific here, so default group is just fine
+            let default_cfg: svaluer::Config =
+                serde_yaml::from_str(include_str!("./default_valuer_config.yaml"))
+                    .expect("default valuer config is invalid");
+            self.valuer_groups.extend(default_cfg.groups);
+            return;
+        }
+        self.custom_valuer_groups = true;
+        // groups that are only mentioned by tests, but not defined in <groups>
+        for test in &testset.tests {
+            let name = test.group.as_deref().unwrap_or("");
+            if !testset.groups.iter().any(|g| g.name == name) {
+                testset.groups.push(PolygonGroup {
+                    name: name.to_string(),
+                    points: None,
+                    points_policy: if has_groups {
+                        "complete-group".to_string()
+                    } else {
+                        "each-test".to_string()
+                    },
+                    feedback_policy: "icpc".to_string(),
+                    deps: Vec::new(),
+                });
+            }
+        }
+        for group in &testset.groups {
+            let group_tests_points: Vec<f64> = testset
+                .tests
+                .iter()
+                .filter(|t| t.group.as_deref().unwrap_or("") == group.name)
+                .map(|t| t.points)
+                .collect();
+            let tests_points: f64 = group_tests_points.iter().sum();
+            let points = group.points.unwrap_or(tests_points);
+            let mut score = if testset.is_main {
+                if points.fract() != 0.0 {
+                    let message = format!(
+                        "group {}: fractional score {} is rounded",
+                        group.name, points
+                    );
+                    self.report(Diagnostic::warning(
+                        DiagnosticCode::UnsupportedFeature,
+                        message,
+                    ))
+                    .await;
+                }
+                points.round() as u32
+            } else {
+                0
+            };
+            let mut scoring = svaluer::cfg::ScoringPolicy::Complete;
+            let mut test_score = None;
+            if group.points_policy == "each-test" && score != 0 && !group_tests_points.is_empty() {
+                let first_points = group_tests_points[0];
+                let same_points = group_tests_points.iter().all(|&p| p == first_points);
+                let points_match = group.points.is_none() || group.points == Some(tests_points);
+                if points_match && same_points && first_points.fract() == 0.0 {
+                    test_score = Some(first_points as u32);
+                    score = 0;
+                } else {
+                    let message = format!(
+                        "group {}: test points can not be represented exactly, group score is proportional to the number of passed tests",
+                        group.name
+                    );
+                    self.report(Diagnostic::warning(
+                        DiagnosticCode::UnsupportedFeature,
+                        message,
+                    ))
+                    .await;
+                    scoring = svaluer::cfg::ScoringPolicy::Partial;
+                }
+            }
+            let feedback = match group.feedback_policy.as_str() {
+                "none" => svaluer::cfg::FeedbackKind::Hidden,
+                "complete" => svaluer::cfg::FeedbackKind::Full,
+                "icpc" => svaluer::cfg::FeedbackKind::Brief,
+                // only group score is shown
+                "points" => svaluer::cfg::FeedbackKind::Custom(svaluer::cfg::CustomFeedback {
+                    tests: Vec::new(),
+                    first_failed_test: Vec::new(),
+                    shown_tests: svaluer::cfg::ShownTests::None,
+                    score: true,
+                }),
+                other => {
+                    let message = format!(
+                        "group {}: unknown feedback-policy {}, brief feedback is used",
+                        group.name, other
+                    );
+                    self.report(Diagnostic::warning(DiagnosticCode::InvalidValue, message))
+                        .await;
+                    svaluer::cfg::FeedbackKind::Brief
+                }
+            };
+            self.valuer_groups.push(svaluer::cfg::Group {
+                name: testset.group_tag(Some(&group.name)),
+                feedback,
+                tests_tag: None,
+                // each passed test gives points
+                run_to_first_failure: test_score.is_none(),
+                score,
+                scoring,
+                deps: group
+                    .deps
+                    .iter()
+                    .map(|dep| svaluer::cfg::GroupRef::ByName(testset.group_tag(Some(dep))))
+                    .collect(),
+                test_score,
+                pass_if_count: None,
+                skip: false,
+                zero_if_failed: false,
+                fraction_policy: None,
+            });
+        }
     }
 
     fn import_file(
@@ -138,7 +596,12 @@ impl<'a> Importer<'a> {
             .map(drop)
     }
 
-    async fn process_file(&mut self, file_path: &str, file_type: &str) -> anyhow::Result<()> {
+    async fn process_file(
+        &mut self,
+        node: roxmltree::Node<'_, '_>,
+        file_path: &str,
+        file_type: &str,
+    ) -> anyhow::Result<()> {
         if !file_path.starts_with("files/") {
             return Ok(());
         }
@@ -160,7 +623,11 @@ impl<'a> Importer<'a> {
                         "couldn't derive file category (stripped name: {})",
                         file_name
                     );
-                    self.pw.send(ImportUpdate::Warning(message)).await;
+                    self.report_at(
+                        node,
+                        Diagnostic::warning(DiagnosticCode::UnexpectedElement, message),
+                    )
+                    .await;
 
                     return Ok(());
                 }
@@ -168,22 +635,35 @@ impl<'a> Importer<'a> {
         };
         match category {
             FileCategory::Validator => {
-                let message = "ignoring validators: not yet implemented".to_string();
-                self.pw.send(ImportUpdate::Warning(message)).await;
+                let message = "ignoring validators: not yet implemented";
+                self.report_at(
+                    node,
+                    Diagnostic::warning(DiagnosticCode::UnsupportedFeature, message),
+                )
+                .await;
             }
             FileCategory::Checker => {
                 // do nothing here, processed separately
             }
             FileCategory::Generator => {
-                let gen_dir = self.dest.join("generators").join(file_name);
-                tokio::fs::create_dir(&gen_dir)
-                    .await
-                    .context("failed to create generator dir")?;
                 let extension = match file_type {
                     _ if file_type.starts_with("cpp.g++") => "cpp",
                     "python.3" => "py",
-                    _ => anyhow::bail!("unknown file type: {}", file_type),
+                    _ => {
+                        let message =
+                            format!("generator {}: unknown file type: {}", file_name, file_type);
+                        self.report_at(
+                            node,
+                            Diagnostic::error(DiagnosticCode::UnsupportedFeature, message),
+                        )
+                        .await;
+                        return Ok(());
+                    }
                 };
+                let gen_dir = self.dest.join("generators").join(file_name);
+                tokio::fs::create_dir(&gen_dir)
+                    .await
+                    .context("failed to create generator dir")?;
                 let dest_path = gen_dir.join(format!("main.{}", extension));
                 let src_path = self.src.join(file_path);
                 tokio::fs::copy(&src_path, &dest_path)
@@ -215,23 +695,83 @@ impl<'a> Importer<'a> {
         node_checker: roxmltree::Node<'_, '_>,
     ) -> anyhow::Result<()> {
         self.pw.send(ImportUpdate::ImportChecker).await;
-        assert_eq!(node_checker.attribute("type"), Some("testlib"));
-        for child in node_checker.children() {
-            if !child.is_element() {
-                continue;
+        let standard_checker = node_checker.attribute("name").and_then(|name| {
+            STANDARD_CHECKERS
+                .iter()
+                .find(|(polygon_name, _, _)| *polygon_name == name)
+        });
+        if let Some((_, builtin_name, args)) = standard_checker {
+            self.pw
+                .send(ImportUpdate::BuiltinChecker(builtin_name.to_string()))
+                .await;
+            self.problem_cfg.builtin_check = Some(crate::manifest::BuiltinCheck {
+                name: builtin_name.to_string(),
+            });
+            self.problem_cfg.check_options = Some(crate::manifest::CheckOptions {
+                args: args.iter().map(ToString::to_string).collect(),
+            });
+            return Ok(());
+        }
+        match self.required_attribute(node_checker, "type").await {
+            Some("testlib") => (),
+            Some(other) => {
+                let message = format!(
+                    "unsupported checker type {}: only testlib checkers can be imported",
+                    other
+                );
+                self.report_at(
+                    node_checker,
+                    Diagnostic::error(DiagnosticCode::UnsupportedFeature, message),
+                )
+                .await;
+                return Ok(());
             }
-            if child.tag_name().name() != "source" {
-                continue;
+            None => return Ok(()),
+        }
+        let node_source = match node_checker
+            .children()
+            .find(|child| child.is_element() && child.tag_name().name() == "source")
+        {
+            Some(node) => node,
+            None => {
+                let message = "<checker> does not have <source> child";
+                self.report_at(
+                    node_checker,
+                    Diagnostic::error(DiagnosticCode::MissingValue, message),
+                )
+                .await;
+                return Ok(());
             }
-            let file_path = child.attribute("path").unwrap();
-            self.import_file(Path::new(file_path), Path::new("modules/checker/main.cpp"))?;
-            let cmakefile = self.dest.join("modules/checker/CMakeLists.txt");
-            let cmakedata =
-                super::template::get_checker_cmakefile(super::template::CheckerOptions {});
-            tokio::fs::write(cmakefile, cmakedata)
-                .await
-                .context("write checker's CMakeLists.txt")?;
+        };
+        let file_path = match self.required_attribute(node_source, "path").await {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let file_type = node_source.attribute("type").unwrap_or("cpp.g++17");
+        if !file_type.starts_with("cpp.") {
+            let message = format!(
+                "unsupported checker source type {}: only C++ checkers can be imported",
+                file_type
+            );
+            self.report_at(
+                node_source,
+                Diagnostic::error(DiagnosticCode::UnsupportedFeature, message),
+            )
+            .await;
+            return Ok(());
+        }
+        if !self.check_file_exists(node_source, file_path).await {
+            return Ok(());
         }
+        tokio::fs::create_dir(self.dest.join("modules/checker"))
+            .await
+            .context("failed to create checker dir")?;
+        self.import_file(Path::new(file_path), Path::new("modules/checker/main.cpp"))?;
+        let cmakefile = self.dest.join("modules/checker/CMakeLists.txt");
+        let cmakedata = super::template::get_checker_cmakefile(super::template::CheckerOptions {});
+        tokio::fs::write(cmakefile, cmakedata)
+            .await
+            .context("write checker's CMakeLists.txt")?;
         Ok(())
     }
 
@@ -243,55 +783,161 @@ impl<'a> Importer<'a> {
             if node_source.tag_name().name() != "source" {
                 continue;
             }
-            let attr_path = node_source
-                .attribute("path")
-                .context("<source> does not have path attribute")?;
-            let attr_type = node_source
-                .attribute("type")
-                .context("<source> does not have type attribute")?;
-            self.process_file(attr_path, attr_type).await?;
+            let attr_path = self.required_attribute(node_source, "path").await;
+            let attr_type = self.required_attribute(node_source, "type").await;
+            if let (Some(attr_path), Some(attr_type)) = (attr_path, attr_type) {
+                if self.check_file_exists(node_source, attr_path).await {
+                    self.process_file(node_source, attr_path, attr_type).await?;
+                }
+            }
         }
         Ok(())
     }
 
-    async fn process_tests(&mut self, tests_node: roxmltree::Node<'_, '_>) -> anyhow::Result<()> {
+    /// Formats path of test `id` by testset path `pattern`.
+    /// Returns None if pattern is invalid, which is reported as error.
+    async fn test_path(
+        &mut self,
+        node_testset: roxmltree::Node<'_, '_>,
+        pattern: &str,
+        id: usize,
+    ) -> Option<String> {
+        match crate::manifest::format_test_path(pattern, id as u32) {
+            Ok(path) => Some(path),
+            Err(err) => {
+                let message = format!("invalid path pattern {}: {:#}", pattern, err);
+                self.report_at(
+                    node_testset,
+                    Diagnostic::error(DiagnosticCode::InvalidValue, message),
+                )
+                .await;
+                None
+            }
+        }
+    }
+
+    async fn process_tests(
+        &mut self,
+        node_testset: roxmltree::Node<'_, '_>,
+        tests_node: roxmltree::Node<'_, '_>,
+        testset: &mut Testset,
+    ) -> anyhow::Result<()> {
         self.pw.send(ImportUpdate::ImportTests).await;
-        assert_eq!(tests_node.tag_name().name(), "tests");
         let mut cnt: usize = 0;
         for test_node in tests_node.children() {
             if !test_node.is_element() {
                 continue;
             }
-            assert_eq!(test_node.tag_name().name(), "test");
+            if test_node.tag_name().name() != "test" {
+                let message = format!("unexpected tag in <tests>: {}", test_node.tag_name().name());
+                self.report_at(
+                    test_node,
+                    Diagnostic::warning(DiagnosticCode::UnexpectedElement, message),
+                )
+                .await;
+                continue;
+            }
             cnt += 1;
+            // tests from all testsets share single numbering
+            let test_id = self.problem_cfg.tests.len() + 1;
+            let group = test_node.attribute("group").map(ToString::to_string);
+            let points = self.parse_points_attribute(test_node).await.unwrap_or(0.0);
+            let method = match self.required_attribute(test_node, "method").await {
+                Some(method) => method,
+                None => continue,
+            };
             let mut ts = crate::manifest::RawTestsSpec {
-                map: cnt.to_string(),
+                map: test_id.to_string(),
                 testgen: None,
                 files: None,
+                answers: None,
                 limits: self.limits,
-                group: format!(
-                    "g{}",
-                    test_node
-                        .attribute("group")
-                        .unwrap_or("default")
-                        .to_string()
-                ),
+                group: testset.group_tag(group.as_deref()),
+                tags: Vec::new(),
             };
-            let is_generated = test_node.attribute("method").unwrap() == "generated";
-            if is_generated {
-                let cmd_iter = test_node.attribute("cmd").unwrap().split_whitespace();
-                let testgen_cmd = cmd_iter.map(ToOwned::to_owned).collect::<Vec<_>>();
-                let gen_name = testgen_cmd[0].clone();
-                self.known_generators.insert(gen_name);
-                ts.testgen = Some(testgen_cmd);
-            } else {
-                // TODO: use formatf here instead of hardcoded format strings
+            let input_path = match self
+                .test_path(node_testset, &testset.input_pattern, cnt)
+                .await
+            {
+                Some(path) => path,
+                // pattern is same for all tests, so there is no point to continue
+                None => return super::check_errors(&self.errors),
+            };
+            let is_generated = method == "generated";
+            let mut import_input = !is_generated;
+            if is_generated && self.static_tests {
+                if self.src.join(&input_path).exists() {
+                    import_input = true;
+                } else {
+                    let message = format!(
+                        "test {}: {} not found, generator will be used",
+                        test_id, input_path
+                    );
+                    self.report_at(
+                        test_node,
+                        Diagnostic::warning(DiagnosticCode::MissingFile, message),
+                    )
+                    .await;
+                }
+            }
+            if import_input {
                 ts.files = Some("%02d.txt".to_string());
-                let src_path = format!("tests/{:0>2}", cnt);
-                let dest_path = format!("tests/{:0>2}.txt", cnt);
-                self.import_file(&src_path, &dest_path)?;
+                if !self.check_file_exists(test_node, &input_path).await {
+                    continue;
+                }
+                let dest_path = format!("tests/{:0>2}.txt", test_id);
+                self.import_file(&input_path, &dest_path)?;
+            } else {
+                let cmd = match self.required_attribute(test_node, "cmd").await {
+                    Some(cmd) => cmd,
+                    None => continue,
+                };
+                let testgen_cmd = cmd
+                    .split_whitespace()
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<_>>();
+                match testgen_cmd.first() {
+                    Some(gen_name) => {
+                        self.known_generators.insert(gen_name.clone());
+                    }
+                    None => {
+                        let message = format!("test {}: generator command is empty", test_id);
+                        self.report_at(
+                            test_node,
+                            Diagnostic::error(DiagnosticCode::InvalidValue, message),
+                        )
+                        .await;
+                        continue;
+                    }
+                }
+                ts.testgen = Some(testgen_cmd);
+            }
+            if self.static_tests {
+                let answer_path = match self
+                    .test_path(node_testset, &testset.answer_pattern, cnt)
+                    .await
+                {
+                    Some(path) => path,
+                    None => return super::check_errors(&self.errors),
+                };
+                if self.src.join(&answer_path).exists() {
+                    ts.answers = Some("%02d.a.txt".to_string());
+                    let dest_path = format!("tests/{:0>2}.a.txt", test_id);
+                    self.import_file(&answer_path, &dest_path)?;
+                } else {
+                    let message = format!(
+                        "test {}: {} not found, answer will be generated by main solution",
+                        test_id, answer_path
+                    );
+                    self.report_at(
+                        test_node,
+                        Diagnostic::warning(DiagnosticCode::MissingFile, message),
+                    )
+                    .await;
+                }
             }
             self.problem_cfg.tests.push(ts);
+            testset.tests.push(PolygonTest { group, points });
         }
         self.pw
             .send(ImportUpdate::ImportTestsDone { count: cnt })
@@ -305,25 +951,38 @@ impl<'a> Importer<'a> {
             if !solution_node.is_element() {
                 continue;
             }
-            let tag = solution_node
-                .attribute("tag")
-                .context("solution does not have <tag> attribute")?;
+            let tag = match self.required_attribute(solution_node, "tag").await {
+                Some(tag) => tag,
+                None => continue,
+            };
             if tag == "main" {
                 self.pw
                     .send(ImportUpdate::ImportSolution(tag.to_string()))
                     .await;
                 self.problem_cfg.primary_solution = Some("main".to_string());
                 let dir = self.dest.join("solutions/main");
-                let mut src_path = None;
-                for child in solution_node.children() {
-                    if !child.is_element() {
+                let node_source = match solution_node
+                    .children()
+                    .find(|child| child.is_element() && child.tag_name().name() == "source")
+                {
+                    Some(node) => node,
+                    None => {
+                        let message = "<solution> does not have <source> child";
+                        self.report_at(
+                            solution_node,
+                            Diagnostic::error(DiagnosticCode::MissingValue, message),
+                        )
+                        .await;
                         continue;
                     }
-                    if child.tag_name().name() == "source" {
-                        src_path = Some(child.attribute("path").unwrap());
-                    }
+                };
+                let src_path = match self.required_attribute(node_source, "path").await {
+                    Some(path) => path,
+                    None => continue,
+                };
+                if !self.check_file_exists(node_source, src_path).await {
+                    continue;
                 }
-                let src_path = src_path.unwrap();
                 tokio::fs::create_dir_all(&dir)
                     .await
                     .context("create main solution dir")?;
@@ -339,21 +998,25 @@ impl<'a> Importer<'a> {
                     "skipping solution with tag {}: importing non-main solutions not yet implemented",
                     tag
                 );
-                self.pw.send(ImportUpdate::Warning(message)).await;
+                self.report_at(
+                    solution_node,
+                    Diagnostic::warning(DiagnosticCode::UnsupportedFeature, message),
+                )
+                .await;
             }
         }
         Ok(())
     }
 
     async fn process_names(&mut self, node_names: roxmltree::Node<'_, '_>) -> anyhow::Result<()> {
-        assert!(node_names.is_element());
         for child in node_names.children() {
             if !child.is_element() {
                 continue;
             }
-            let title = child
-                .attribute("value")
-                .context("<name> does not have value attribute")?;
+            let title = match self.required_attribute(child, "value").await {
+                Some(title) => title,
+                None => continue,
+            };
 
             self.problem_cfg.title = title.to_string();
             self.pw
@@ -368,7 +1031,6 @@ impl<'a> Importer<'a> {
     }
 
     fn process_problem(&mut self, node_problem: roxmltree::Node) {
-        assert_eq!(node_problem.tag_name().name(), "problem");
         if let Some(name) = node_problem.attribute("short-name") {
             self.problem_cfg.name = name.to_string();
         }
@@ -385,31 +1047,35 @@ impl<'a> Importer<'a> {
             args: vec!["assets/module-checker/bin".to_string()],
         });
         m.valuer_cfg = Some("valuer.yaml".to_string());
-        let mut random_seed = [0; 8];
-        getrandom::getrandom(&mut random_seed)?;
-        let rand_seed_hex = hex::encode(&random_seed);
-        assert_eq!(rand_seed_hex.len(), 16);
-        m.random_seed = Some(rand_seed_hex);
+        m.random_seed = Some(super::generate_random_seed()?);
         Ok(())
     }
 
-    fn init_dirs(&mut self) -> anyhow::Result<()> {
-        for suf in &[
-            "solutions",
-            "generators",
-            "tests",
-            "modules",
-            "modules/checker",
-        ] {
-            let path = self.dest.join(suf);
-            std::fs::create_dir(&path).with_context(|| format!("create {}", path.display()))?;
+    async fn import_testlib(&mut self) -> anyhow::Result<()> {
+        if self.src.join("files/testlib.h").exists() {
+            self.import_file(Path::new("files/testlib.h"), Path::new("testlib.h"))?;
+        } else {
+            let message = "files/testlib.h not found";
+            self.report(Diagnostic::error(DiagnosticCode::MissingFile, message))
+                .await;
         }
-
-        // import testlib
-        self.import_file(Path::new("files/testlib.h"), Path::new("testlib.h"))?;
         Ok(())
     }
 
+    /// Checks that file `path` referenced by `node` exists, reporting an error otherwise
+    async fn check_file_exists(&mut self, node: roxmltree::Node<'_, '_>, path: &str) -> bool {
+        if self.src.join(path).exists() {
+            return true;
+        }
+        let message = format!("{} not found", path);
+        self.report_at(
+            node,
+            Diagnostic::error(DiagnosticCode::MissingFile, message),
+        )
+        .await;
+        false
+    }
+
     fn go<'b>(
         &'b mut self,
         node: roxmltree::Node<'b, 'b>,
@@ -444,15 +1110,17 @@ impl<'a> Importer<'a> {
         let valuer_cfg_path = self.src.join("files/valuer.cfg");
         let config = if valuer_cfg_path.exists() {
             self.pw.send(ImportUpdate::ImportValuerConfig).await;
-            let (config, warnings) = super::valuer_cfg::import(&valuer_cfg_path).await?;
-            for warn in warnings {
-                self.pw
-                    .send(ImportUpdate::Warning(format!(
-                        "while importing valuer config: {}",
-                        warn
-                    )))
-                    .await;
+            let imported = super::valuer_cfg::import(&valuer_cfg_path, "files/valuer.cfg").await?;
+            for diagnostic in imported.diagnostics {
+                self.report(diagnostic).await;
             }
+            serde_yaml::to_string(&imported.config)?
+        } else if self.custom_valuer_groups {
+            self.pw.send(ImportUpdate::GenerateValuerConfig).await;
+            let config = svaluer::Config {
+                groups: std::mem::take(&mut self.valuer_groups),
+                ..Default::default()
+            };
             serde_yaml::to_string(&config)?
         } else {
             self.pw.send(ImportUpdate::DefaultValuerConfig).await;
@@ -463,10 +1131,276 @@ impl<'a> Importer<'a> {
     }
 
     pub(crate) async fn run(&mut self) -> anyhow::Result<()> {
-        self.init_dirs()?;
+        super::init_dirs(self.dest)?;
+        self.import_testlib().await?;
         self.fill_manifest()?;
         self.feed(self.doc).await?;
         self.import_valuer_config().await?;
-        Ok(())
+        super::check_errors(&self.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svaluer::cfg::{FeedbackKind, GroupRef, ScoringPolicy, ShownTests};
+
+    fn test(group: Option<&str>, points: f64) -> PolygonTest {
+        PolygonTest {
+            group: group.map(ToString::to_string),
+            points,
+        }
+    }
+
+    fn group(name: &str, points: Option<f64>, points_policy: &str) -> PolygonGroup {
+        PolygonGroup {
+            name: name.to_string(),
+            points,
+            points_policy: points_policy.to_string(),
+            feedback_policy: "complete".to_string(),
+            deps: Vec::new(),
+        }
+    }
+
+    fn testset(is_main: bool, tests: Vec<PolygonTest>, groups: Vec<PolygonGroup>) -> Testset {
+        Testset {
+            name: if is_main { "tests" } else { "pretests" }.to_string(),
+            is_main,
+            input_pattern: "tests/%02d".to_string(),
+            answer_pattern: "tests/%02d.a".to_string(),
+            tests,
+            groups,
+        }
+    }
+
+    fn importer<'a>(
+        doc: &'a roxmltree::Document<'a>,
+        pw: &'a mut ProgressWriter<ImportUpdate>,
+    ) -> Importer<'a> {
+        Importer {
+            src: Path::new("."),
+            dest: Path::new("."),
+            problem_cfg: Default::default(),
+            known_generators: HashSet::new(),
+            doc: doc.root_element(),
+            limits: pom::Limits::default(),
+            pw,
+            valuer_groups: Vec::new(),
+            custom_valuer_groups: false,
+            static_tests: false,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Returns generated groups and whether they differ from default config
+    async fn convert(testset: Testset) -> (Vec<svaluer::cfg::Group>, bool) {
+        let doc = roxmltree::Document::parse("<problem/>").unwrap();
+        let mut pw = crate::test_util::discard_progress();
+        let mut importer = importer(&doc, &mut pw);
+        importer.process_testset_groups(testset).await;
+        assert!(importer.errors.is_empty());
+        (importer.valuer_groups, importer.custom_valuer_groups)
+    }
+
+    /// (name, score, test score, scoring)
+    fn summary(group: &svaluer::cfg::Group) -> (&str, u32, Option<u32>, ScoringPolicy) {
+        (&group.name, group.score, group.test_score, group.scoring)
+    }
+
+    #[tokio::test]
+    async fn no_groups_and_points() {
+        let (groups, custom) = convert(testset(true, vec![test(None, 0.0)], Vec::new())).await;
+        assert!(!custom);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            summary(&groups[0]),
+            ("default", 100, None, ScoringPolicy::Complete)
+        );
+    }
+
+    #[tokio::test]
+    async fn points_without_groups() {
+        let tests = vec![test(None, 10.0), test(None, 10.0)];
+        let (groups, custom) = convert(testset(true, tests, Vec::new())).await;
+        assert!(custom);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            summary(&groups[0]),
+            ("default", 0, Some(10), ScoringPolicy::Complete)
+        );
+        assert!(!groups[0].run_to_first_failure);
+    }
+
+    #[tokio::test]
+    async fn each_test_policy() {
+        let tests = vec![
+            test(Some("1"), 5.0),
+            test(Some("1"), 5.0),
+            test(Some("2"), 3.0),
+            test(Some("2"), 7.0),
+            test(Some("3"), 2.5),
+            test(Some("3"), 2.5),
+        ];
+        let groups = vec![
+            group("1", None, "each-test"),
+            group("2", None, "each-test"),
+            group("3", None, "each-test"),
+        ];
+        let (groups, _) = convert(testset(true, tests, groups)).await;
+        let summaries: Vec<_> = groups.iter().map(summary).collect();
+        assert_eq!(
+            summaries,
+            [
+                // equal integer points are given per test
+                ("g1", 0, Some(5), ScoringPolicy::Complete),
+                // otherwise score is proportional to passed tests
+                ("g2", 10, None, ScoringPolicy::Partial),
+                ("g3", 5, None, ScoringPolicy::Partial),
+            ]
+        );
+        assert!(!groups[0].run_to_first_failure);
+        assert!(groups[1].run_to_first_failure);
+    }
+
+    #[tokio::test]
+    async fn complete_group_policy() {
+        let tests = vec![
+            test(Some("0"), 0.0),
+            test(Some("1"), 0.0),
+            test(Some("1"), 0.0),
+            test(Some("2"), 0.0),
+            test(Some("3"), 0.0),
+        ];
+        let mut second = group("2", Some(60.6), "complete-group");
+        second.deps = vec!["1".to_string()];
+        second.feedback_policy = "none".to_string();
+        let mut first = group("1", Some(40.0), "complete-group");
+        first.feedback_policy = "icpc".to_string();
+        let mut third = group("3", Some(0.0), "complete-group");
+        third.feedback_policy = "points".to_string();
+        let groups = vec![group("0", None, "complete-group"), first, second, third];
+        let (groups, custom) = convert(testset(true, tests, groups)).await;
+        assert!(custom);
+        let summaries: Vec<_> = groups.iter().map(summary).collect();
+        assert_eq!(
+            summaries,
+            [
+                ("g0", 0, None, ScoringPolicy::Complete),
+                ("g1", 40, None, ScoringPolicy::Complete),
+                // fractional score is rounded
+                ("g2", 61, None, ScoringPolicy::Complete),
+                ("g3", 0, None, ScoringPolicy::Complete),
+            ]
+        );
+        assert!(matches!(groups[0].feedback, FeedbackKind::Full));
+        assert!(matches!(groups[1].feedback, FeedbackKind::Brief));
+        assert!(matches!(groups[2].feedback, FeedbackKind::Hidden));
+        assert!(matches!(
+            &groups[3].feedback,
+            FeedbackKind::Custom(custom)
+                if custom.score && custom.shown_tests == ShownTests::None && custom.tests.is_empty()
+        ));
+        assert!(matches!(&groups[2].deps[..], [GroupRef::ByName(dep)] if dep == "g1"));
+    }
+
+    #[tokio::test]
+    async fn undeclared_groups() {
+        // group `2` is only mentioned by tests
+        let tests = vec![test(Some("1"), 0.0), test(Some("2"), 0.0)];
+        let groups = vec![group("1", Some(100.0), "complete-group")];
+        let (groups, _) = convert(testset(true, tests, groups)).await;
+        let summaries: Vec<_> = groups.iter().map(summary).collect();
+        assert_eq!(
+            summaries,
+            [
+                ("g1", 100, None, ScoringPolicy::Complete),
+                ("g2", 0, None, ScoringPolicy::Complete),
+            ]
+        );
+        assert!(matches!(groups[1].feedback, FeedbackKind::Brief));
+    }
+
+    #[tokio::test]
+    async fn secondary_testset() {
+        let tests = vec![test(Some("1"), 10.0), test(Some("1"), 10.0)];
+        let mut dependent = group("2", Some(50.0), "complete-group");
+        dependent.deps = vec!["1".to_string()];
+        let groups = vec![group("1", None, "each-test"), dependent];
+        let (groups, custom) = convert(testset(false, tests, groups)).await;
+        assert!(custom);
+        let summaries: Vec<_> = groups.iter().map(summary).collect();
+        // only main testset gives points
+        assert_eq!(
+            summaries,
+            [
+                ("pretests-g1", 0, None, ScoringPolicy::Complete),
+                ("pretests-g2", 0, None, ScoringPolicy::Complete),
+            ]
+        );
+        assert!(matches!(&groups[1].deps[..], [GroupRef::ByName(dep)] if dep == "pretests-g1"));
+    }
+
+    #[tokio::test]
+    async fn secondary_testset_limits() {
+        let doc = roxmltree::Document::parse(
+            r#"<judging>
+    <testset name="pretests">
+        <time-limit>2000</time-limit>
+        <memory-limit>268435456</memory-limit>
+    </testset>
+    <testset name="tests">
+        <time-limit>1000</time-limit>
+        <memory-limit>268435456</memory-limit>
+    </testset>
+</judging>"#,
+        )
+        .unwrap();
+        let doc = &doc;
+        let (limits, updates) = crate::test_util::collect_progress(|mut pw| async move {
+            let mut importer = importer(doc, &mut pw);
+            importer
+                .process_judging_section(doc.root_element())
+                .await
+                .unwrap();
+            importer.limits
+        })
+        .await;
+        // main testset is not the first one
+        assert_eq!(limits.time, Some(1000));
+        let diagnostics: Vec<_> = updates
+            .iter()
+            .filter_map(|update| match update {
+                ImportUpdate::Diagnostic(d) => {
+                    Some((d.code, d.location.as_ref().map(|loc| loc.line)))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(diagnostics, [(DiagnosticCode::UnsupportedFeature, Some(2))]);
+    }
+
+    #[tokio::test]
+    async fn invalid_path_pattern() {
+        let doc = roxmltree::Document::parse(
+            r#"<testset name="tests">
+    <input-path-pattern>tests/%d-%d</input-path-pattern>
+    <tests>
+        <test method="manual"/>
+        <test method="manual"/>
+    </tests>
+</testset>"#,
+        )
+        .unwrap();
+        let mut pw = crate::test_util::discard_progress();
+        let mut importer = importer(&doc, &mut pw);
+        let err = importer
+            .process_testset(doc.root_element(), true)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("invalid path pattern tests/%d-%d"));
+        // pattern is reported once, at the testset
+        assert_eq!(importer.errors.len(), 1);
+        assert_eq!(importer.errors[0].code, DiagnosticCode::InvalidValue);
+        assert_eq!(importer.errors[0].location.as_ref().unwrap().line, 1);
     }
 }
@@ -0,0 +1,241 @@
+//! Parser for ejudge `serve.cfg`.
+//! Config consists of global options, followed by sections like `[problem]`.
+//! Each line is either `key = value` or just `key` (boolean flag).
+use crate::apis::import::{Diagnostic, DiagnosticCode, SourceLocation};
+
+pub(crate) const FILE_NAME: &str = "conf/serve.cfg";
+
+pub(crate) fn location(line: u32) -> SourceLocation {
+    SourceLocation {
+        file: FILE_NAME.to_string(),
+        line,
+        column: 1,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    pub(crate) key: String,
+    /// None for flags without value
+    pub(crate) value: Option<String>,
+    pub(crate) line: u32,
+}
+
+#[derive(Debug)]
+pub(crate) struct Section {
+    /// Section name without brackets. Empty for global options.
+    pub(crate) name: String,
+    pub(crate) line: u32,
+    pub(crate) entries: Vec<Entry>,
+}
+
+impl Section {
+    pub(crate) fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().rev().find(|entry| entry.key == key)
+    }
+}
+
+/// Parses quoted string, returning its contents
+fn parse_quoted(value: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = value[1..].chars();
+    loop {
+        match chars.next() {
+            None => return Err("unterminated string".to_string()),
+            Some('"') => break,
+            Some('\\') => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(c) => out.push(c),
+                None => return Err("unterminated string".to_string()),
+            },
+            Some(c) => out.push(c),
+        }
+    }
+    let rest = chars.as_str().trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(format!("unexpected characters after string: {}", rest));
+    }
+    Ok(out)
+}
+
+fn parse_value(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    if value.starts_with('"') {
+        return parse_quoted(value);
+    }
+    let value = match value.find('#') {
+        Some(pos) => value[..pos].trim_end(),
+        None => value,
+    };
+    Ok(value.to_string())
+}
+
+/// Splits config into sections.
+/// Malformed lines are reported as diagnostics and skipped.
+pub(crate) fn parse(input: &str) -> (Vec<Section>, Vec<Diagnostic>) {
+    let mut sections = vec![Section {
+        name: String::new(),
+        line: 1,
+        entries: Vec::new(),
+    }];
+    let mut diagnostics = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line_num = idx as u32 + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') {
+            match line.find(']') {
+                Some(end) => sections.push(Section {
+                    name: line[1..end].trim().to_string(),
+                    line: line_num,
+                    entries: Vec::new(),
+                }),
+                None => {
+                    let message = format!("section header is not closed: {}", line);
+                    diagnostics.push(
+                        Diagnostic::error(DiagnosticCode::SyntaxError, message)
+                            .at(location(line_num)),
+                    );
+                }
+            }
+            continue;
+        }
+        let (key, value) = match line.find('=') {
+            Some(pos) => (line[..pos].trim(), Some(&line[pos + 1..])),
+            None => (line, None),
+        };
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            let message = format!("invalid option name: {}", key);
+            diagnostics.push(
+                Diagnostic::error(DiagnosticCode::SyntaxError, message).at(location(line_num)),
+            );
+            continue;
+        }
+        let value = match value.map(parse_value).transpose() {
+            Ok(value) => value,
+            Err(err) => {
+                let message = format!("option {}: {}", key, err);
+                diagnostics.push(
+                    Diagnostic::error(DiagnosticCode::SyntaxError, message).at(location(line_num)),
+                );
+                continue;
+            }
+        };
+        sections
+            .last_mut()
+            .expect("global section is always present")
+            .entries
+            .push(Entry {
+                key: key.to_string(),
+                value,
+                line: line_num,
+            });
+    }
+    (sections, diagnostics)
+}
+
+/// Parses size with optional `K`, `M` or `G` suffix
+pub(crate) fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (num, mult) = match value.chars().last()?.to_ascii_uppercase() {
+        'K' => (&value[..value.len() - 1], 1 << 10),
+        'M' => (&value[..value.len() - 1], 1 << 20),
+        'G' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    num.trim().parse::<u64>().ok()?.checked_mul(mult)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections_and_values() {
+        let (sections, diagnostics) = parse(
+            r#"
+# global options
+contest_time = 300
+advanced_layout
+
+[problem]
+short_name = "A"  # comment
+long_name = "Say \"hi\"\n"
+time_limit = 2 # seconds
+; another comment
+[ language ]
+id = 1
+"#,
+        );
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(sections.len(), 3);
+        let global = &sections[0];
+        assert_eq!(global.name, "");
+        assert_eq!(
+            global.get("contest_time").unwrap().value.as_deref(),
+            Some("300")
+        );
+        assert_eq!(global.get("advanced_layout").unwrap().value, None);
+        let problem = &sections[1];
+        assert_eq!(problem.name, "problem");
+        assert_eq!(problem.line, 6);
+        assert_eq!(
+            problem.get("short_name").unwrap().value.as_deref(),
+            Some("A")
+        );
+        assert_eq!(
+            problem.get("long_name").unwrap().value.as_deref(),
+            Some("Say \"hi\"\n")
+        );
+        assert_eq!(
+            problem.get("time_limit").unwrap().value.as_deref(),
+            Some("2")
+        );
+        assert_eq!(problem.get("time_limit").unwrap().line, 9);
+        assert_eq!(sections[2].name, "language");
+    }
+
+    #[test]
+    fn last_value_wins() {
+        let (sections, _) = parse("[problem]\nid = 1\nid = 2\n");
+        assert_eq!(sections[1].get("id").unwrap().value.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn malformed_lines() {
+        let (sections, diagnostics) = parse(
+            r#"[problem
+bad-key = 1
+title = "unterminated
+title = "a" b
+ok = 1
+"#,
+        );
+        let lines: Vec<_> = diagnostics
+            .iter()
+            .map(|d| d.location.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, [1, 2, 3, 4]);
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code == DiagnosticCode::SyntaxError));
+        // malformed lines are skipped, the rest is parsed
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].entries.len(), 1);
+        assert_eq!(sections[0].entries[0].key, "ok");
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("64K"), Some(64 << 10));
+        assert_eq!(parse_size("256m"), Some(256 << 20));
+        assert_eq!(parse_size("2G"), Some(2 << 30));
+        assert_eq!(parse_size("G"), None);
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("12X"), None);
+    }
+}
@@ -0,0 +1,305 @@
+//! Incremental re-import.
+//! Import records hashes of all files it created. When package is imported
+//! again, upstream changes are applied only to files which were not edited locally.
+use super::{ImportUpdate, SyncAction};
+use crate::{manifest::RawProblem, operation::ProgressWriter};
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use sha1::Digest as _;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+/// Name of the file which stores import state, relative to package root
+const STATE_FILE: &str = ".pps-import.json";
+
+/// Suffix of files which store upstream version of conflicting files
+const UPSTREAM_SUFFIX: &str = ".upstream";
+
+#[derive(Serialize, Deserialize, Default)]
+struct ImportState {
+    /// Maps path relative to package root to SHA-1 hash of the imported file
+    files: BTreeMap<String, String>,
+}
+
+fn hash_file(path: &Path) -> anyhow::Result<String> {
+    let data = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(hex::encode(sha1::Sha1::digest(&data)))
+}
+
+/// Hashes all files in the `root`, except import state
+fn hash_tree(root: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut out = BTreeMap::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            let name = path
+                .strip_prefix(root)
+                .expect("path is inside root")
+                .to_string_lossy()
+                .to_string();
+            if name == STATE_FILE {
+                continue;
+            }
+            out.insert(name, hash_file(&path)?);
+        }
+    }
+    Ok(out)
+}
+
+fn hash_if_exists(path: &Path) -> anyhow::Result<Option<String>> {
+    if path.is_file() {
+        hash_file(path).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+fn read_state(dest: &Path) -> anyhow::Result<Option<ImportState>> {
+    let path = dest.join(STATE_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data =
+        std::fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let state = serde_json::from_slice(&data)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(Some(state))
+}
+
+fn save_state(dest: &Path, state: &ImportState) -> anyhow::Result<()> {
+    let path = dest.join(STATE_FILE);
+    std::fs::write(&path, serde_json::to_vec_pretty(state)?)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Records all files of freshly imported package `dest`
+pub(crate) fn write_state(dest: &Path) -> anyhow::Result<()> {
+    let state = ImportState {
+        files: hash_tree(dest)?,
+    };
+    save_state(dest, &state)
+}
+
+/// Makes manifests in `imported` use random seeds of the existing problems,
+/// so that generated tests do not change after sync.
+/// Returns paths of rewritten manifests.
+fn keep_random_seeds(
+    imported: &Path,
+    dest: &Path,
+    files: &BTreeMap<String, String>,
+) -> anyhow::Result<Vec<String>> {
+    let mut changed = Vec::new();
+    for name in files.keys() {
+        if Path::new(name).file_name() != Some(std::ffi::OsStr::new("problem.toml")) {
+            continue;
+        }
+        let existing = match std::fs::read_to_string(dest.join(name)) {
+            Ok(data) => data,
+            Err(_) => continue,
+        };
+        let existing: RawProblem = match toml::from_str(&existing) {
+            Ok(problem) => problem,
+            Err(_) => continue,
+        };
+        let manifest_path = imported.join(name);
+        let mut problem: RawProblem = toml::from_str(&std::fs::read_to_string(&manifest_path)?)
+            .with_context(|| format!("failed to parse imported {}", name))?;
+        if existing.random_seed.is_some() {
+            problem.random_seed = existing.random_seed;
+            super::write_problem_manifest(
+                manifest_path.parent().expect("path is not empty"),
+                &problem,
+            )?;
+            changed.push(name.clone());
+        }
+    }
+    Ok(changed)
+}
+
+/// Merges freshly imported package `imported` into previously imported package `dest`.
+/// File is updated if it changed upstream and was not edited locally.
+/// If both versions changed, local version is kept and upstream version is
+/// saved next to it with `.upstream` suffix.
+pub(crate) async fn apply(
+    imported: &Path,
+    dest: &Path,
+    pw: &mut ProgressWriter<ImportUpdate>,
+) -> anyhow::Result<()> {
+    let state = match read_state(dest)? {
+        Some(state) => state,
+        None => {
+            if dest.exists() && std::fs::read_dir(dest)?.next().is_some() {
+                anyhow::bail!(
+                    "{} does not contain {}, so it can not be synced; import into empty dir instead",
+                    dest.display(),
+                    STATE_FILE
+                );
+            }
+            ImportState::default()
+        }
+    };
+    let mut upstream = hash_tree(imported)?;
+    for path in keep_random_seeds(imported, dest, &upstream)? {
+        let hash = hash_file(&imported.join(&path))?;
+        upstream.insert(path, hash);
+    }
+    let paths: BTreeSet<&String> = state.files.keys().chain(upstream.keys()).collect();
+    for path in paths {
+        let base = state.files.get(path);
+        let new = upstream.get(path);
+        if base == new {
+            continue;
+        }
+        let dest_path = dest.join(path);
+        let local = hash_if_exists(&dest_path)?;
+        if local.as_ref() == new {
+            // same change was made locally
+            continue;
+        }
+        let action = if local.as_ref() == base {
+            match new {
+                Some(_) => {
+                    std::fs::create_dir_all(dest_path.parent().expect("path is not empty"))?;
+                    std::fs::copy(imported.join(path), &dest_path)
+                        .with_context(|| format!("failed to update {}", path))?;
+                    if base.is_some() {
+                        SyncAction::Updated
+                    } else {
+                        SyncAction::Added
+                    }
+                }
+                None => {
+                    std::fs::remove_file(&dest_path)
+                        .with_context(|| format!("failed to remove {}", path))?;
+                    SyncAction::Removed
+                }
+            }
+        } else {
+            if new.is_some() {
+                let upstream_path = dest.join(format!("{}{}", path, UPSTREAM_SUFFIX));
+                std::fs::create_dir_all(upstream_path.parent().expect("path is not empty"))?;
+                std::fs::copy(imported.join(path), &upstream_path)
+                    .with_context(|| format!("failed to save upstream version of {}", path))?;
+            }
+            SyncAction::Conflict
+        };
+        pw.send(ImportUpdate::SyncFile {
+            path: path.clone(),
+            action,
+        })
+        .await;
+    }
+    // conflicting files are considered resolved in favor of local version
+    save_state(dest, &ImportState { files: upstream })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{collect_progress, write};
+
+    fn read(root: &Path, name: &str) -> Option<String> {
+        std::fs::read_to_string(root.join(name)).ok()
+    }
+
+    /// Runs `apply` and returns reported actions
+    async fn run_apply(imported: &Path, dest: &Path) -> anyhow::Result<Vec<(String, SyncAction)>> {
+        let (res, updates) =
+            collect_progress(|mut pw| async move { apply(imported, dest, &mut pw).await }).await;
+        let actions = updates
+            .into_iter()
+            .filter_map(|update| match update {
+                ImportUpdate::SyncFile { path, action } => Some((path, action)),
+                _ => None,
+            })
+            .collect();
+        res.map(|_| actions)
+    }
+
+    #[tokio::test]
+    async fn merge_decisions() {
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path();
+        for name in &[
+            "same",
+            "updated",
+            "conflict",
+            "removed",
+            "edited-removed",
+            "both",
+            "local",
+        ] {
+            write(dest, name, "base");
+        }
+        write(dest, "dir/nested", "base");
+        write_state(dest).unwrap();
+        // local edits
+        write(dest, "conflict", "local");
+        write(dest, "edited-removed", "local");
+        write(dest, "both", "new");
+        write(dest, "local", "local");
+
+        let imported = tempfile::tempdir().unwrap();
+        let imported = imported.path();
+        write(imported, "same", "base");
+        write(imported, "updated", "new");
+        write(imported, "conflict", "new");
+        write(imported, "both", "new");
+        write(imported, "local", "base");
+        write(imported, "added", "new");
+        write(imported, "dir/nested", "new");
+
+        let actions = run_apply(imported, dest).await.unwrap();
+        assert_eq!(
+            actions,
+            [
+                ("added".to_string(), SyncAction::Added),
+                ("conflict".to_string(), SyncAction::Conflict),
+                ("dir/nested".to_string(), SyncAction::Updated),
+                ("edited-removed".to_string(), SyncAction::Conflict),
+                ("removed".to_string(), SyncAction::Removed),
+                ("updated".to_string(), SyncAction::Updated),
+            ]
+        );
+        assert_eq!(read(dest, "same").as_deref(), Some("base"));
+        assert_eq!(read(dest, "updated").as_deref(), Some("new"));
+        assert_eq!(read(dest, "added").as_deref(), Some("new"));
+        assert_eq!(read(dest, "dir/nested").as_deref(), Some("new"));
+        assert_eq!(read(dest, "removed"), None);
+        assert_eq!(read(dest, "both").as_deref(), Some("new"));
+        assert_eq!(read(dest, "local").as_deref(), Some("local"));
+        // local version is kept, upstream one is saved next to it
+        assert_eq!(read(dest, "conflict").as_deref(), Some("local"));
+        assert_eq!(read(dest, "conflict.upstream").as_deref(), Some("new"));
+        assert_eq!(read(dest, "edited-removed").as_deref(), Some("local"));
+        assert_eq!(read(dest, "edited-removed.upstream"), None);
+
+        // conflicts are resolved in favor of local version,
+        // so syncing same upstream again changes nothing
+        let actions = run_apply(imported, dest).await.unwrap();
+        assert_eq!(actions, []);
+    }
+
+    #[tokio::test]
+    async fn dest_without_state() {
+        let imported = tempfile::tempdir().unwrap();
+        write(imported.path(), "file", "new");
+
+        let dest = tempfile::tempdir().unwrap();
+        write(dest.path(), "file", "local");
+        assert!(run_apply(imported.path(), dest.path()).await.is_err());
+
+        // empty dir is treated as empty package
+        let empty = tempfile::tempdir().unwrap();
+        let actions = run_apply(imported.path(), empty.path()).await.unwrap();
+        assert_eq!(actions, [("file".to_string(), SyncAction::Added)]);
+        assert!(empty.path().join(STATE_FILE).exists());
+    }
+}
@@ -8,3 +8,10 @@ pub struct CheckerOptions {}
 pub fn get_checker_cmakefile(_options: CheckerOptions) -> String {
     CHECKER_TPL.to_string()
 }
+
+static OUTPUT_VALIDATOR_TPL: &str = include_str!("output_validator_tpl.cmake");
+
+/// Kattis output validator may consist of several source files
+pub fn get_output_validator_cmakefile() -> String {
+    OUTPUT_VALIDATOR_TPL.to_string()
+}
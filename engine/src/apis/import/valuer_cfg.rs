@@ -1,16 +1,23 @@
 // triggers on Pest-generated code
 #![allow(clippy::upper_case_acronyms)]
 
+use crate::apis::import::{Diagnostic, DiagnosticCode, SourceLocation};
 use pest::Parser as _;
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub(crate) enum ImportValuerCfgError {
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
-    #[error("valuer.cfg has syntax error: {0}")]
-    Syntax(#[from] pest::error::Error<Rule>),
+}
+
+fn location(file: &str, (line, column): (usize, usize)) -> SourceLocation {
+    SourceLocation {
+        file: file.to_string(),
+        line: line as u32,
+        column: column as u32,
+    }
 }
 
 use pest_derive::Parser;
@@ -19,24 +26,50 @@ use pest_derive::Parser;
 #[grammar = "apis/import/valuer_cfg.pest"]
 pub(super) struct P;
 
+/// Group options which depend on global options
+#[derive(Default)]
+struct GroupVisibility {
+    offline: bool,
+    stat_to_users: bool,
+}
+
 struct Visitor<'a> {
+    file: &'a str,
     config: &'a mut svaluer::Config,
-    tests_info: std::collections::HashMap<u32, String>,
-    warnings: Vec<String>,
+    /// Visibility options of each group in `config`
+    visibility: Vec<GroupVisibility>,
+    /// Set by global `stat_to_users` option
+    stat_to_users: bool,
+    tests_info: HashMap<u32, String>,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl<'a> Visitor<'a> {
-    fn warn(&mut self, msg: String) {
-        self.warnings.push(msg);
+    fn report(&mut self, node: &pest::iterators::Pair<'a, Rule>, diagnostic: Diagnostic) {
+        let loc = location(self.file, node.as_span().start_pos().line_col());
+        self.diagnostics.push(diagnostic.at(loc));
     }
 
-    fn warn_not_sup(&mut self, feat: &str) {
-        self.warn(format!("not supported feature: {}", feat));
+    fn warn_not_sup(&mut self, node: &pest::iterators::Pair<'a, Rule>, reason: &str) {
+        let message = format!("not supported feature: {}: {}", node.as_str(), reason);
+        self.report(
+            node,
+            Diagnostic::warning(DiagnosticCode::UnsupportedFeature, message),
+        );
     }
 
-    fn visit_global_def(&mut self, _node: pest::iterators::Pair<'a, Rule>) {
-        // all global options are not supported currently
-        self.warn_not_sup("GlobalDefinitions");
+    fn visit_global_def(&mut self, node: pest::iterators::Pair<'a, Rule>) {
+        for global_option in node.into_inner() {
+            // option is enabled unless its value is 0
+            let enabled = !matches!(
+                global_option.clone().into_inner().next(),
+                Some(num) if num.as_str() == "0"
+            );
+            if global_option.as_str().starts_with("stat_to_users") {
+                self.stat_to_users = enabled;
+            }
+            // stat_to_judges: judges always see full judge log
+        }
     }
 
     fn visit_group_def(&mut self, node: pest::iterators::Pair<'a, Rule>) {
@@ -44,35 +77,61 @@ impl<'a> Visitor<'a> {
         let mut iter = node.into_inner();
         let group_num_node = iter.next().unwrap();
         assert_eq!(group_num_node.as_rule(), Rule::num);
-        let num: u32 = group_num_node.as_str().parse().unwrap();
+        let num: u32 = group_num_node
+            .as_str()
+            .parse()
+            .expect("grammar limits num length");
         let mut group_cfg = svaluer::cfg::Group {
             name: format!("g{}", num),
             feedback: svaluer::cfg::FeedbackKind::Brief,
             tests_tag: None,
-            run_to_first_failure: false,
+            run_to_first_failure: true,
             score: 0,
+            scoring: svaluer::cfg::ScoringPolicy::Complete,
             deps: vec![],
+            test_score: None,
+            pass_if_count: None,
+            skip: false,
+            zero_if_failed: false,
+            fraction_policy: None,
         };
+        let mut visibility = GroupVisibility::default();
         for group_option in iter {
-            self.visit_group_option(group_option, &mut group_cfg);
+            self.visit_group_option(group_option, &mut group_cfg, &mut visibility);
         }
         self.config.groups.push(group_cfg);
+        self.visibility.push(visibility);
     }
 
     fn visit_group_option(
         &mut self,
         node: pest::iterators::Pair<'a, Rule>,
         group: &mut svaluer::cfg::Group,
+        visibility: &mut GroupVisibility,
     ) {
         match node.as_rule() {
             Rule::group_option_tests => {
-                let mut iter = node.into_inner();
+                let mut iter = node.clone().into_inner();
                 let num1: u32 = iter.next().unwrap().as_str().parse().unwrap();
                 let num2: u32 = iter.next().unwrap().as_str().parse().unwrap();
-                assert!(num1 <= num2);
+                if num1 > num2 {
+                    let message = format!(
+                        "group {}: invalid tests range {}-{}",
+                        group.name, num1, num2
+                    );
+                    self.report(
+                        &node,
+                        Diagnostic::error(DiagnosticCode::InvalidValue, message),
+                    );
+                    return;
+                }
                 for tid in num1..=num2 {
                     if self.tests_info.insert(tid, group.name.clone()).is_some() {
-                        self.warn(format!("test {} is mentioned more than once", tid));
+                        let message = format!("test {} is mentioned more than once", tid);
+                        self.report(
+                            &node,
+                            Diagnostic::warning(DiagnosticCode::Inconsistency, message),
+                        );
                     }
                 }
             }
@@ -90,16 +149,60 @@ impl<'a> Visitor<'a> {
                         .push(svaluer::cfg::GroupRef::ByName(dep_group_name));
                 }
             }
-            Rule::group_option_sets_marked_if_passed => {
-                self.warn_not_sup("GroupOptionSetsMarkedIfPassed");
+            Rule::group_option_test_score => {
+                let sc = node.into_inner().next().unwrap().as_str().parse().unwrap();
+                group.test_score = Some(sc);
+                // every test contributes to score, so all of them are run
+                group.run_to_first_failure = false;
+            }
+            Rule::group_option_pass_if_count => {
+                let count = node.into_inner().next().unwrap().as_str().parse().unwrap();
+                group.pass_if_count = Some(count);
+            }
+            Rule::group_option_test_all => {
+                group.run_to_first_failure = false;
+            }
+            Rule::group_option_skip => {
+                group.skip = true;
+            }
+            Rule::group_option_zero_if => {
+                group.zero_if_failed = true;
             }
             Rule::group_option_offline => {
-                group.feedback = svaluer::cfg::FeedbackKind::Hidden;
+                visibility.offline = true;
             }
-            Rule::group_option_test_score => {
-                self.warn_not_sup("GroupOptionTestScore");
+            Rule::group_option_stat_to_users => {
+                visibility.stat_to_users = true;
+            }
+            Rule::group_option_stat_to_judges => {
+                // judges always see full judge log
+            }
+            Rule::group_option_sets_marked_if_passed | Rule::group_option_sets_marked => {
+                self.warn_not_sup(&node, "submissions can not be marked");
+            }
+            Rule::group_option_skip_if_not_rejudge => {
+                self.warn_not_sup(
+                    &node,
+                    "valuer does not know about rejudges, group is always run",
+                );
             }
-            other => panic!("{:?}", other),
+            Rule::group_option_user_status => {
+                self.warn_not_sup(&node, "custom statuses are not supported");
+            }
+            other => unreachable!("unexpected group option: {:?}", other),
+        }
+    }
+
+    /// Sets feedback of groups once global options are known
+    fn apply_visibility(&mut self) {
+        for (group, visibility) in self.config.groups.iter_mut().zip(&self.visibility) {
+            group.feedback = if visibility.offline {
+                svaluer::cfg::FeedbackKind::Hidden
+            } else if visibility.stat_to_users || self.stat_to_users {
+                svaluer::cfg::FeedbackKind::Full
+            } else {
+                svaluer::cfg::FeedbackKind::Brief
+            };
         }
     }
 
@@ -110,7 +213,7 @@ impl<'a> Visitor<'a> {
                     match child.as_rule() {
                         Rule::EOI => (),
                         Rule::definition => self.visit(child),
-                        other => panic!("{:?}", other),
+                        other => unreachable!("unexpected definition: {:?}", other),
                     }
                 }
             }
@@ -121,26 +224,68 @@ impl<'a> Visitor<'a> {
                     Rule::group_def => {
                         self.visit_group_def(child);
                     }
-                    other => panic!("{:?}", other),
+                    other => unreachable!("unexpected definition: {:?}", other),
                 }
             }
-            _ => panic!("{:?}", node),
+            _ => unreachable!("unexpected node: {:?}", node),
         }
     }
 }
 
+/// Result of valuer config translation
+pub(crate) struct ImportedValuerCfg {
+    pub(crate) config: svaluer::Config,
+    /// Maps test id to name of group containing this test
+    pub(crate) test_groups: HashMap<u32, String>,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+}
+
+/// Translates ejudge valuer config.
+/// Syntax errors and unsupported features are returned as diagnostics,
+/// `file` is path to config relative to package root used in them.
 pub(crate) async fn import(
     path: &Path,
-) -> Result<(svaluer::Config, Vec<String>), ImportValuerCfgError> {
+    file: &str,
+) -> Result<ImportedValuerCfg, ImportValuerCfgError> {
     let input = tokio::fs::read_to_string(path).await?;
-    let mut ast = P::parse(Rule::config, &input)?;
-    let mut config = svaluer::Config { groups: Vec::new() };
+    let mut config = svaluer::Config::default();
+    let mut ast = match P::parse(Rule::config, &input) {
+        Ok(ast) => ast,
+        Err(err) => {
+            let line_col = match err.line_col {
+                pest::error::LineColLocation::Pos(pos) => pos,
+                pest::error::LineColLocation::Span(start, _) => start,
+            };
+            let message = match err.variant {
+                pest::error::ErrorVariant::ParsingError { positives, .. } => {
+                    format!("syntax error: expected one of {:?}", positives)
+                }
+                pest::error::ErrorVariant::CustomError { message } => message,
+            };
+            let diagnostic = Diagnostic::error(DiagnosticCode::SyntaxError, message)
+                .at(location(file, line_col));
+            return Ok(ImportedValuerCfg {
+                config,
+                test_groups: HashMap::new(),
+                diagnostics: vec![diagnostic],
+            });
+        }
+    };
     let mut visitor = Visitor {
+        file,
         config: &mut config,
-        tests_info: std::collections::HashMap::new(),
-        warnings: Vec::new(),
+        visibility: Vec::new(),
+        stat_to_users: false,
+        tests_info: HashMap::new(),
+        diagnostics: Vec::new(),
     };
     visitor.visit(ast.next().unwrap());
-    let warnings = std::mem::take(&mut visitor.warnings);
-    Ok((config, warnings))
+    visitor.apply_visibility();
+    let diagnostics = std::mem::take(&mut visitor.diagnostics);
+    let test_groups = std::mem::take(&mut visitor.tests_info);
+    Ok(ImportedValuerCfg {
+        config,
+        test_groups,
+        diagnostics,
+    })
 }
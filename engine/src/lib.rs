@@ -2,6 +2,8 @@ pub mod apis;
 mod command;
 mod manifest;
 pub mod operation;
+#[cfg(test)]
+mod test_util;
 
 use std::path::Path;
 
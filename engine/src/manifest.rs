@@ -27,9 +27,27 @@ pub struct RawTestsSpec {
     pub map: String,
     pub testgen: Option<Vec<String>>,
     pub files: Option<String>,
+    /// Printf-style pattern of correct answer files.
+    /// If not specified, answers are generated by primary solution.
+    pub answers: Option<String>,
     #[serde(default)]
     pub limits: pom::Limits,
     pub group: String,
+    /// Additional tags. Tests are included into each group whose tag is
+    /// either `group` or one of `tags`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Interpolates test id into printf-style pattern
+pub(crate) fn format_test_path(tpl: &str, id: u32) -> anyhow::Result<String> {
+    match formatf::format(tpl.as_bytes(), &[formatf::Value::Int(i128::from(id))]) {
+        Ok(path) => Ok(String::from_utf8(path).expect("interpolation provided non-utf8 data")),
+        Err(err) => {
+            bail!("formatting error: {:?}", err);
+            // TODO: implement Display for formatf FormatError
+        }
+    }
 }
 
 impl RawTestsSpec {
@@ -97,19 +115,8 @@ impl RawTestsSpec {
         let mut out = Vec::new();
         if let Some(file_tpl) = &self.files {
             for &id in idxs.iter() {
-                let res =
-                    formatf::format(file_tpl.as_bytes(), &[formatf::Value::Int(i128::from(id))]);
-                match res {
-                    Ok(file) => {
-                        let file =
-                            String::from_utf8(file).expect("interpolation provided non-utf8 data");
-                        out.push((id, TestGenSpec::File { path: file }));
-                    }
-                    Err(err) => {
-                        bail!("formatting error: {:?}", err);
-                        // TODO: implement Display for formatf FormatError
-                    }
-                }
+                let file = format_test_path(file_tpl, id)?;
+                out.push((id, TestGenSpec::File { path: file }));
             }
         }
         if let Some(testgen_cmd) = &self.testgen {
@@ -122,15 +129,26 @@ impl RawTestsSpec {
                 out.push((id, spec.clone()));
             }
         }
+        let mut answers = Vec::new();
+        for &id in &idxs {
+            let answer = match &self.answers {
+                Some(answer_tpl) => Some(format_test_path(answer_tpl, id)?),
+                None => None,
+            };
+            answers.push(answer);
+        }
         let out = out
             .into_iter()
-            .map(|(id, test_gen_spec)| {
+            .zip(answers)
+            .map(|((id, test_gen_spec), answer)| {
                 (
                     id,
                     TestSpec {
                         gen: test_gen_spec,
+                        answer,
                         limits: self.limits,
                         group: self.group.clone(),
+                        tags: self.tags.clone(),
                     },
                 )
             })
@@ -149,8 +167,11 @@ pub enum TestGenSpec {
 #[derive(Debug)]
 pub struct TestSpec {
     pub gen: TestGenSpec,
+    /// Path to correct answer, relative to `tests` dir
+    pub answer: Option<String>,
     pub limits: pom::Limits,
     pub group: String,
+    pub tags: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -298,3 +319,72 @@ pub struct Problem {
     pub valuer_cfg: Option<String>,
     pub limits: pom::Limits,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContestProblem {
+    /// Problem code shown to participants, e.g. `A`
+    pub code: String,
+    pub name: String,
+    /// Path to problem source, relative to contest dir
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RawContest {
+    pub title: String,
+
+    pub name: String,
+
+    /// Problems in the order they are shown to participants
+    pub problems: Vec<ContestProblem>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_patterns() {
+        let spec = RawTestsSpec {
+            map: "1..2,10".to_string(),
+            files: Some("%02d".to_string()),
+            answers: Some("%02d.a".to_string()),
+            group: "main".to_string(),
+            ..Default::default()
+        };
+        let tests = spec.postprocess().unwrap();
+        let ids: Vec<_> = tests.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [1, 2, 10]);
+        let (_, last) = &tests[2];
+        assert!(matches!(&last.gen, TestGenSpec::File { path } if path == "10"));
+        assert_eq!(last.answer.as_deref(), Some("10.a"));
+        assert_eq!(last.group, "main");
+    }
+
+    #[test]
+    fn generated_answers() {
+        let spec = RawTestsSpec {
+            map: "3".to_string(),
+            testgen: Some(vec!["gen".to_string(), "5".to_string()]),
+            group: "main".to_string(),
+            ..Default::default()
+        };
+        let tests = spec.postprocess().unwrap();
+        assert_eq!(tests.len(), 1);
+        let (id, test) = &tests[0];
+        assert_eq!(*id, 3);
+        assert!(
+            matches!(&test.gen, TestGenSpec::Generate { testgen, args } if testgen == "gen" && args == &["5"])
+        );
+        assert_eq!(test.answer, None);
+    }
+
+    #[test]
+    fn test_path_formatting() {
+        assert_eq!(
+            format_test_path("tests/%03d.in", 7).unwrap(),
+            "tests/007.in"
+        );
+        assert_eq!(format_test_path("%d", 12).unwrap(), "12");
+    }
+}
@@ -0,0 +1,33 @@
+//! Fixtures shared by unit tests
+use crate::operation::ProgressWriter;
+use std::{future::Future, path::Path};
+
+/// Returns progress writer which discards all updates
+pub(crate) fn discard_progress<U>() -> ProgressWriter<U> {
+    let (_, pw) = crate::operation::start();
+    pw
+}
+
+/// Runs `f` and returns its output together with all updates it sent
+pub(crate) async fn collect_progress<U, F, Fut>(f: F) -> (Fut::Output, Vec<U>)
+where
+    F: FnOnce(ProgressWriter<U>) -> Fut,
+    Fut: Future,
+{
+    let (mut op, pw) = crate::operation::start();
+    let collect = async move {
+        let mut updates = Vec::new();
+        while let Some(update) = op.next_update().await {
+            updates.push(update);
+        }
+        updates
+    };
+    tokio::join!(f(pw), collect)
+}
+
+/// Writes `data` to file `name` under `root`, creating parent dirs
+pub(crate) fn write(root: &Path, name: &str, data: &str) {
+    let path = root.join(name);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, data).unwrap();
+}
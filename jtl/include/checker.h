@@ -61,6 +61,12 @@ enum class Outcome {
 /// internal judging error will be diagnosed
 void finish(Outcome outcome) ATTR_NORETURN;
 
+/// Same as `finish`, but also reports that solution gained
+/// `numerator / denominator` of the test score.
+/// Fraction must not exceed 1.
+void finish_with_fraction(Outcome outcome, uint64_t numerator,
+                          uint64_t denominator) ATTR_NORETURN;
+
 /// Some comparison functions
 
 bool compare_epsilon(long double expected, long double actual,
@@ -13,7 +13,13 @@ struct Args {
 };
 
 bool is_float(const char* s) {
+    if (s[0] == '-' || s[0] == '+') {
+        ++s;
+    }
     const size_t n = strlen(s);
+    if (n == 0) {
+        return false;
+    }
     size_t cnt = 0;
     for (size_t i = 0; i < n; ++i) {
         if (s[i] != '.' && (s[i] < '0' || s[i] > '9')) {
@@ -0,0 +1,111 @@
+#include <cassert>
+#include <dirent.h>
+#include <string>
+#include <vector>
+#include <wait.h>
+
+#include "checker.h"
+#include "unistd.h"
+#include "util.h"
+
+using namespace checker;
+
+static const size_t PATH_LEN = 128;
+
+// Kattis output validator exit codes
+static const int EXIT_AC = 42;
+static const int EXIT_WA = 43;
+
+/// Removes feedback dir together with files written by validator
+static void remove_feedback_dir(const char* path) {
+    DIR* dir = opendir(path);
+    if (dir == nullptr) {
+        return;
+    }
+    while (dirent* entry = readdir(dir)) {
+        std::string name = entry->d_name;
+        if (name == "." || name == "..") {
+            continue;
+        }
+        unlink((std::string(path) + "/" + name).c_str());
+    }
+    closedir(dir);
+    rmdir(path);
+}
+
+int main(int argc, char** argv) {
+    if (argc < 2) {
+        fprintf(stderr,
+                "Usage: %s path_to_kattis_output_validator [validator_flags...]",
+                argv[0]);
+        exit(1);
+    }
+    CheckerInput input = init(false);
+    char input_file[PATH_LEN];
+    char answer_file[PATH_LEN];
+
+    pid_t my_pid = getpid();
+    assert(my_pid != -1);
+
+    sprintf(input_file, "/proc/%d/fd/%d", my_pid, (int) input.fd_test);
+    sprintf(answer_file, "/proc/%d/fd/%d", my_pid, (int) input.fd_corr);
+
+    char feedback_dir[] = "/tmp/kattis-feedback-XXXXXX";
+    if (mkdtemp(feedback_dir) == nullptr) {
+        fprintf(stderr, "error: failed to create feedback dir: %m\n");
+        exit(1);
+    }
+
+    char* inner_checker = argv[1];
+    std::vector<char*> child_argv = {inner_checker, input_file, answer_file,
+                                     feedback_dir};
+    for (int i = 2; i < argc; ++i) {
+        child_argv.push_back(argv[i]);
+    }
+    child_argv.push_back(nullptr);
+
+    int fres = fork();
+    if (fres == -1) {
+        fprintf(stderr, "fork() failed: %m\n");
+        return 1;
+    }
+    if (fres == 0) {
+        // validator reads contestant's output from stdin
+        if (dup2((int) input.fd_sol, 0) == -1) {
+            fprintf(stderr, "error: dup2() failed: %m\n");
+            exit(66);
+        }
+        execv(inner_checker, child_argv.data());
+        fprintf(stderr, "error: launch inner checker %s: %d (%m)\n",
+                inner_checker, errno);
+        exit(66);
+    }
+    int wstatus;
+    if (waitpid(fres, &wstatus, 0) == -1) {
+        fprintf(stderr, "error: waitpid() failed: %m\n");
+        exit(1);
+    }
+    remove_feedback_dir(feedback_dir);
+    if (WIFEXITED(wstatus)) {
+        int exit_code = WEXITSTATUS(wstatus);
+        switch (exit_code) {
+        case EXIT_AC:
+            finish(Outcome::OK);
+            break;
+        case EXIT_WA:
+            finish(Outcome::WRONG_ANSWER);
+            break;
+        default:
+            fprintf(stderr, "unexpected return code from child checker: %d\n",
+                    exit_code);
+            finish(Outcome::CHECKER_LOGIC_ERROR);
+        }
+    } else {
+        fprintf(
+            stderr,
+            "unexpected exit status (child checker didn't terminate normally): "
+            "%d\n",
+            wstatus);
+        exit(1);
+    }
+}
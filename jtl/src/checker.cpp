@@ -30,7 +30,8 @@ checker::CheckerInput checker::init(bool open_files) {
     return inp;
 }
 
-void checker::finish(Outcome outcome) {
+static void write_outcome(checker::Outcome outcome) {
+    using checker::Outcome;
     FILE* proto_file = CHECKER.out_file;
     fprintf(proto_file, "outcome=");
     switch (outcome) {
@@ -47,7 +48,24 @@ void checker::finish(Outcome outcome) {
         fprintf(proto_file, "PresentationError");
         break;
     }
+}
+
+void checker::finish(Outcome outcome) {
+    write_outcome(outcome);
+    exit(0);
+}
 
+void checker::finish_with_fraction(Outcome outcome, uint64_t numerator,
+                                   uint64_t denominator) {
+    if (denominator == 0 || numerator > denominator) {
+        fprintf(stderr, "invalid fraction %llu/%llu\n",
+                (unsigned long long)numerator,
+                (unsigned long long)denominator);
+        finish(Outcome::CHECKER_LOGIC_ERROR);
+    }
+    write_outcome(outcome);
+    fprintf(CHECKER.out_file, "\nfraction=%llu/%llu",
+            (unsigned long long)numerator, (unsigned long long)denominator);
     exit(0);
 }
 
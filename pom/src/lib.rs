@@ -77,6 +77,9 @@ pub struct Test {
     pub correct: Option<FileRef>,
     pub limits: Limits,
     pub group: String,
+    /// Additional group tags
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize, Hash)]
@@ -8,6 +8,54 @@ pub enum FeedbackKind {
     Brief,
     /// Full feedback is provided
     Full,
+    /// Feedback is configured component by component
+    Custom(CustomFeedback),
+}
+
+/// Part of test information which can be shown to contestant
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum TestComponent {
+    TestData,
+    Output,
+    Answer,
+    Status,
+    ResourceUsage,
+}
+
+/// Determines which tests of the group are included into contestant judge log
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShownTests {
+    All,
+    /// Only the failed test with the smallest id
+    FirstFailed,
+    None,
+}
+
+fn default_shown_tests() -> ShownTests {
+    ShownTests::All
+}
+
+fn default_show_score() -> bool {
+    true
+}
+
+/// Fine-grained feedback settings
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CustomFeedback {
+    /// Components shown for each test
+    #[serde(default)]
+    pub tests: Vec<TestComponent>,
+    /// Components additionally shown for the first failed test
+    #[serde(default)]
+    pub first_failed_test: Vec<TestComponent>,
+    /// Tests included into judge log
+    #[serde(default = "default_shown_tests")]
+    pub shown_tests: ShownTests,
+    /// Show score gained for the group
+    #[serde(default = "default_show_score")]
+    pub score: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -17,6 +65,34 @@ pub enum GroupRef {
     ById(u32),
 }
 
+/// Determines how `score` of the group is awarded
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScoringPolicy {
+    /// Score is given only if group is passed
+    Complete,
+    /// Score is proportional to the number of passed tests (rounded down).
+    /// All tests of the group are run.
+    Partial,
+}
+
+/// Determines how fractions of test score reported by checker are combined
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum FractionPolicy {
+    /// Only `test_score` is affected. `score` is awarded according to
+    /// `scoring`, so this policy requires `test_score`.
+    Sum,
+    /// `score` is multiplied by the minimal fraction over tests
+    Min,
+    /// `score` is multiplied by the average fraction over tests
+    Average,
+}
+
+fn default_scoring() -> ScoringPolicy {
+    ScoringPolicy::Complete
+}
+
 fn default_run_to_first_failure() -> bool {
     true
 }
@@ -35,9 +111,34 @@ pub struct Group {
     pub run_to_first_failure: bool,
     /// Group score
     pub score: u32,
+    /// How `score` is awarded
+    #[serde(default = "default_scoring")]
+    pub scoring: ScoringPolicy,
     /// Required groups
     #[serde(default)]
     pub deps: Vec<GroupRef>,
+    /// Score for each passed test.
+    /// It is given in addition to `score`, even if group is failed.
+    #[serde(default)]
+    pub test_score: Option<u32>,
+    /// Group is passed if at least this number of tests passed.
+    /// By default all tests must pass.
+    #[serde(default)]
+    pub pass_if_count: Option<u32>,
+    /// Do not run this group. It gives no score and
+    /// does not block groups depending on it.
+    #[serde(default)]
+    pub skip: bool,
+    /// If this group is not passed, total score is zero
+    #[serde(default)]
+    pub zero_if_failed: bool,
+    /// If set, fractions of test score reported by checker are used.
+    /// Tests without fraction count as 1 if passed and 0 otherwise.
+    /// Each test gives `test_score` multiplied by its fraction, whatever
+    /// the policy is; the policy determines how `score` is given.
+    /// All tests of the group are run.
+    #[serde(default)]
+    pub fraction_policy: Option<FractionPolicy>,
 }
 
 impl Group {
@@ -49,15 +150,33 @@ impl Group {
 /// SValuer config
 /// # Offline tests
 /// For offline tests, contestant is not provided with feedback.
-/// To activate, set `open_tests_count` and `open_tests_score`.
+/// To activate, set `open_tests_count` and/or `open_groups_count`.
+/// Groups which are not open are judged as if their feedback was `hidden`.
+/// Tests which are not open are excluded from contestant judge log, and
+/// groups containing them are valued over their open tests only.
 #[derive(Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct Config {
     pub groups: Vec<Group>,
+    /// Only first `open_tests_count` tests are open
+    #[serde(default)]
+    pub open_tests_count: Option<u32>,
+    /// Only first `open_groups_count` groups are open
+    #[serde(default)]
+    pub open_groups_count: Option<u32>,
+    /// Limits number of tests running at once and enables parallel execution:
+    /// tests of a group are started without waiting for previous ones (their
+    /// results are still processed in test order), and while fewer than
+    /// `max_parallel_tests` tests are running, tests which may be needed later
+    /// (e.g. tests of groups waiting for deps) are started in advance. Their
+    /// results are used if group actually requests them, and discarded otherwise.
+    #[serde(default)]
+    pub max_parallel_tests: Option<u32>,
 }
 
 const MSG_INVALID_GROUP_REF: &str = "GroupRef refers to nonexistent group";
 const MSG_CIRCULAR_REF: &str = "group dependencies have cycle";
+const MSG_SUM_WITHOUT_TEST_SCORE: &str = "fraction_policy `sum` requires test_score";
 
 fn dfs(graph: &[Vec<usize>], used: &mut [u8], has_cycle: &mut bool, v: usize) {
     used[v] = 1;
@@ -85,6 +204,24 @@ impl Config {
         }
     }
 
+    /// Checks whether contestant gets feedback for the group with index `group`
+    /// during contest
+    pub fn is_open(&self, group: usize) -> bool {
+        match self.open_groups_count {
+            Some(count) => group < count as usize,
+            None => true,
+        }
+    }
+
+    /// Checks whether contestant gets feedback for the test with id `test`
+    /// during contest
+    pub fn is_test_open(&self, test: u32) -> bool {
+        match self.open_tests_count {
+            Some(count) => test <= count,
+            None => true,
+        }
+    }
+
     pub fn validate(&self, error_sink: &mut Vec<String>) {
         let mut group_dep_graph: Vec<Vec<usize>> = Vec::new();
 
@@ -106,12 +243,17 @@ impl Config {
         let mut used = vec![0; self.groups.len()];
         for i in 0..self.groups.len() {
             if used[i] == 0 {
-                dfs(&group_dep_graph, &mut used, &mut has_cycle, 0);
+                dfs(&group_dep_graph, &mut used, &mut has_cycle, i);
             }
         }
         if has_cycle {
             error_sink.push(MSG_CIRCULAR_REF.to_string());
         }
+        for g in &self.groups {
+            if g.fraction_policy == Some(FractionPolicy::Sum) && g.test_score.is_none() {
+                error_sink.push(MSG_SUM_WITHOUT_TEST_SCORE.to_string());
+            }
+        }
     }
 }
 
@@ -195,5 +337,42 @@ groups:
                 &[MSG_CIRCULAR_REF],
             )
         }
+
+        #[test]
+        fn test_circular_ref_not_reachable_from_first() {
+            check_errs(
+                "
+groups:
+  - name: foo
+    feedback: full
+    score: 50
+  - name: bar
+    feedback: hidden
+    score: 50
+    deps:
+      - bar
+            ",
+                &[MSG_CIRCULAR_REF],
+            )
+        }
+
+        #[test]
+        fn test_sum_without_test_score() {
+            check_errs(
+                "
+groups:
+  - name: foo
+    feedback: full
+    score: 50
+    fraction_policy: sum
+  - name: bar
+    feedback: full
+    score: 50
+    test_score: 5
+    fraction_policy: sum
+            ",
+                &[MSG_SUM_WITHOUT_TEST_SCORE],
+            )
+        }
     }
 }
@@ -0,0 +1,305 @@
+//! Config explorer: values many pass/fail patterns of tests
+//! to find mistakes in valuer config, such as unreachable groups or scores.
+use crate::{status_util, Config, SimpleValuer, ValuerDriver};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::{BTreeSet, VecDeque};
+use valuer_api::{
+    JudgeLog, JudgeLogKind, ProblemInfo, SubtaskState, TestDoneNotification, ValuerResponse,
+};
+
+/// If problem has at most this number of tests, all patterns are checked.
+pub const MAX_EXHAUSTIVE_TESTS: usize = 16;
+
+/// Result of config exploration
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Count of valued patterns
+    pub patterns: usize,
+    /// True if all possible patterns were valued.
+    /// Otherwise, results are only lower bounds.
+    pub exhaustive: bool,
+    /// Score of solution passing all tests.
+    /// Includes `test_score` of each test; skipped groups are not counted.
+    pub nominal_max_score: u32,
+    /// All scores which were seen, in ascending order
+    pub reachable_scores: Vec<u32>,
+    /// True if some pattern produced full solution
+    pub full_reachable: bool,
+    /// Names of groups which were never run (neither passed nor failed).
+    /// Groups with `skip: true` are not included.
+    pub never_run_groups: Vec<String>,
+}
+
+impl Report {
+    /// Returns human-readable descriptions of problems found
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for name in &self.never_run_groups {
+            warnings.push(format!("group {} is never run", name));
+        }
+        let max_score = self.reachable_scores.last().copied().unwrap_or(0);
+        if max_score != self.nominal_max_score {
+            warnings.push(format!(
+                "maximal reachable score is {}, but groups can give {}",
+                max_score, self.nominal_max_score
+            ));
+        }
+        if !self.full_reachable {
+            warnings.push("full solution is unreachable".to_string());
+        }
+        warnings
+    }
+}
+
+/// Driver which reports each test as passed or failed, according to `pattern`
+#[derive(Debug)]
+struct PatternDriver<'a> {
+    problem_info: &'a ProblemInfo,
+    /// `pattern[i]` is true if test `i + 1` passes
+    pattern: &'a [bool],
+    pending_notifications: VecDeque<TestDoneNotification>,
+    full_judge_log: Option<JudgeLog>,
+}
+
+impl ValuerDriver for PatternDriver<'_> {
+    fn problem_info(&mut self) -> Result<ProblemInfo> {
+        Ok(self.problem_info.clone())
+    }
+
+    fn send_command(&mut self, cmd: &ValuerResponse) -> Result<()> {
+        match cmd {
+            ValuerResponse::Test { test_id, .. } => {
+                let test_status = if self.pattern[test_id.to_idx()] {
+                    status_util::make_ok_status()
+                } else {
+                    status_util::make_err_status()
+                };
+                self.pending_notifications.push_back(TestDoneNotification {
+                    test_id: *test_id,
+                    test_status,
+                    fraction: None,
+                    resource_usage: None,
+                });
+            }
+            ValuerResponse::CancelTest { test_id } => {
+                self.pending_notifications
+                    .retain(|tdn| tdn.test_id != *test_id);
+            }
+            ValuerResponse::JudgeLog(judge_log) => {
+                if judge_log.kind == JudgeLogKind::Full {
+                    self.full_judge_log = Some(judge_log.clone());
+                }
+            }
+            ValuerResponse::LiveScore { .. }
+            | ValuerResponse::Snapshot { .. }
+            | ValuerResponse::Finish => (),
+        }
+        Ok(())
+    }
+
+    fn poll_notification(&mut self) -> Result<Option<TestDoneNotification>> {
+        Ok(self.pending_notifications.pop_front())
+    }
+}
+
+/// Simple xorshift generator: sampling does not need good randomness,
+/// but must be reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Patterns which are checked when exhaustive search is impossible:
+/// all passed, all failed, every single failed test and `samples` random ones.
+fn sampled_patterns(test_count: usize, samples: usize) -> Vec<Vec<bool>> {
+    let mut patterns = vec![vec![true; test_count], vec![false; test_count]];
+    for i in 0..test_count {
+        let mut pattern = vec![true; test_count];
+        pattern[i] = false;
+        patterns.push(pattern);
+    }
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..samples {
+        // random failure probability, so that both mostly-passing
+        // and mostly-failing solutions are represented
+        let threshold = rng.next() % 101;
+        let pattern = (0..test_count)
+            .map(|_| rng.next() % 100 >= threshold)
+            .collect();
+        patterns.push(pattern);
+    }
+    patterns
+}
+
+fn exhaustive_patterns(test_count: usize) -> Vec<Vec<bool>> {
+    (0..1u32 << test_count)
+        .map(|mask| (0..test_count).map(|i| mask & (1 << i) != 0).collect())
+        .collect()
+}
+
+/// Computes maximal score which groups of `cfg` can give
+fn nominal_max_score(cfg: &Config, problem_info: &ProblemInfo) -> u32 {
+    cfg.groups
+        .iter()
+        .filter(|group| !group.skip)
+        .map(|group| {
+            let tests_count = (0..problem_info.tests.len())
+                .filter(|&i| problem_info.test_has_tag(i, group.tests_tag()))
+                .count() as u32;
+            group.score + group.test_score.unwrap_or(0) * tests_count
+        })
+        .sum()
+}
+
+/// Values `cfg` on many pass/fail patterns of tests.
+/// If there are more than `MAX_EXHAUSTIVE_TESTS` tests, `samples` random patterns
+/// are used instead of all patterns.
+pub fn explore(cfg: &Config, problem_info: &ProblemInfo, samples: usize) -> Result<Report> {
+    let mut errors = Vec::new();
+    cfg.validate(&mut errors);
+    if !errors.is_empty() {
+        bail!("invalid config: {}", errors.join(", "));
+    }
+    let test_count = problem_info.tests.len();
+    let exhaustive = test_count <= MAX_EXHAUSTIVE_TESTS;
+    let patterns = if exhaustive {
+        exhaustive_patterns(test_count)
+    } else {
+        sampled_patterns(test_count, samples)
+    };
+
+    let mut reachable_scores = BTreeSet::new();
+    let mut full_reachable = false;
+    let mut group_was_run = vec![false; cfg.groups.len()];
+    for pattern in &patterns {
+        let mut driver = PatternDriver {
+            problem_info,
+            pattern,
+            pending_notifications: VecDeque::new(),
+            full_judge_log: None,
+        };
+        SimpleValuer::new(&mut driver, cfg)?.exec()?;
+        let judge_log = match driver.full_judge_log {
+            Some(log) => log,
+            None => bail!("valuer did not emit full judge log"),
+        };
+        reachable_scores.insert(judge_log.score);
+        full_reachable |= judge_log.is_full;
+        for row in &judge_log.subtasks {
+            if matches!(row.state, SubtaskState::Passed | SubtaskState::Failed) {
+                group_was_run[row.subtask_id.0.get() as usize - 1] = true;
+            }
+        }
+    }
+
+    let never_run_groups = cfg
+        .groups
+        .iter()
+        .zip(group_was_run)
+        .filter(|(group, was_run)| !group.skip && !was_run)
+        .map(|(group, _)| group.name.clone())
+        .collect();
+    Ok(Report {
+        patterns: patterns.len(),
+        exhaustive,
+        nominal_max_score: nominal_max_score(cfg, problem_info),
+        reachable_scores: reachable_scores.into_iter().collect(),
+        full_reachable,
+        never_run_groups,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn explore_yaml(cfg: &str, tests: &[&str]) -> Report {
+        crate::setup_log();
+        let cfg: Config = serde_yaml::from_str(cfg).expect("failed to parse config");
+        let problem_info = ProblemInfo {
+            tests: tests.iter().map(|s| s.to_string()).collect(),
+            test_tags: Vec::new(),
+        };
+        explore(&cfg, &problem_info, 0).unwrap()
+    }
+
+    #[test]
+    fn correct_config() {
+        let report = explore_yaml(
+            "
+groups:
+  - name: samples
+    feedback: full
+    score: 0
+  - name: main
+    feedback: brief
+    score: 100
+    deps:
+      - samples
+",
+            &["samples", "main", "main"],
+        );
+        assert!(report.exhaustive);
+        assert_eq!(report.patterns, 8);
+        assert_eq!(report.reachable_scores, vec![0, 100]);
+        assert!(report.full_reachable);
+        assert!(report.never_run_groups.is_empty());
+        assert!(report.warnings().is_empty());
+    }
+
+    #[test]
+    fn unreachable_group() {
+        let report = explore_yaml(
+            "
+groups:
+  - name: first
+    feedback: brief
+    score: 40
+    pass_if_count: 3
+  - name: second
+    feedback: brief
+    score: 60
+    deps:
+      - first
+",
+            &["first", "first", "second"],
+        );
+        assert_eq!(report.reachable_scores, vec![0]);
+        assert!(!report.full_reachable);
+        assert_eq!(report.never_run_groups, vec!["second".to_string()]);
+        assert_eq!(report.warnings().len(), 3);
+    }
+
+    #[test]
+    fn per_test_scores() {
+        let report = explore_yaml(
+            "
+groups:
+  - name: samples
+    feedback: full
+    score: 0
+  - name: main
+    feedback: full
+    score: 0
+    test_score: 5
+    run_to_first_failure: false
+  - name: extra
+    feedback: hidden
+    score: 20
+    skip: true
+",
+            &["samples", "main", "main"],
+        );
+        assert_eq!(report.nominal_max_score, 10);
+        assert_eq!(report.reachable_scores, vec![0, 5, 10]);
+        assert!(report.full_reachable);
+        assert!(report.warnings().is_empty());
+    }
+}
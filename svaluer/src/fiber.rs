@@ -1,15 +1,20 @@
 mod group;
 
-use crate::cfg::Config;
+use crate::cfg::{Config, FeedbackKind, ShownTests, TestComponent};
 use group::Group;
 use pom::TestId;
-use std::{collections::HashSet, num::NonZeroU32};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    num::NonZeroU32,
+};
 use valuer_api::{
-    JudgeLog, JudgeLogKind, ProblemInfo, Status, SubtaskVisibleComponents, TestVisibleComponents,
+    JudgeLog, JudgeLogKind, JudgeLogTestRow, ProblemInfo, ResourceUsage, Status, SubtaskId,
+    SubtaskVisibleComponents, TestVisibleComponents,
 };
 /// Creates single JudgeLog
 /// SValuer works by aggegating several fibers (one per judgelog kind).
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct Fiber {
     kind: JudgeLogKind,
     /// If test is not in this set, it will not be included into judge log.
@@ -17,6 +22,10 @@ pub(crate) struct Fiber {
     // contains indices for `groups`
     active_groups: Vec<usize>,
     groups: Vec<Group>,
+    /// Some groups or tests are not visible for this judge log kind, so
+    /// their outcome must not be disclosed
+    #[serde(default)]
+    has_hidden_tests: bool,
     finished: bool,
     last_live_score: u32,
 }
@@ -35,9 +44,43 @@ enum GroupVisPreset {
     Full,
     Brief,
     Hidden,
+    Custom {
+        tests: TestVisibleComponents,
+        first_failed_test: TestVisibleComponents,
+        shown_tests: ShownTests,
+        score: bool,
+    },
+}
+
+fn test_components(components: &[TestComponent]) -> TestVisibleComponents {
+    let mut out = TestVisibleComponents::empty();
+    for component in components {
+        out |= match component {
+            TestComponent::TestData => TestVisibleComponents::TEST_DATA,
+            TestComponent::Output => TestVisibleComponents::OUTPUT,
+            TestComponent::Answer => TestVisibleComponents::ANSWER,
+            TestComponent::Status => TestVisibleComponents::STATUS,
+            TestComponent::ResourceUsage => TestVisibleComponents::RESOURCE_USAGE,
+        };
+    }
+    out
 }
 
 impl GroupVisPreset {
+    fn from_feedback(feedback: &FeedbackKind) -> GroupVisPreset {
+        match feedback {
+            FeedbackKind::Brief => GroupVisPreset::Brief,
+            FeedbackKind::Full => GroupVisPreset::Full,
+            FeedbackKind::Hidden => GroupVisPreset::Hidden,
+            FeedbackKind::Custom(custom) => GroupVisPreset::Custom {
+                tests: test_components(&custom.tests),
+                first_failed_test: test_components(&custom.first_failed_test),
+                shown_tests: custom.shown_tests,
+                score: custom.score,
+            },
+        }
+    }
+
     fn subtask_flags_for(self, k: JudgeLogKind) -> SubtaskVisibleComponents {
         let mut out = SubtaskVisibleComponents::empty();
         if self == GroupVisPreset::Full || k == JudgeLogKind::Full {
@@ -46,6 +89,9 @@ impl GroupVisPreset {
         if self == GroupVisPreset::Brief || k == JudgeLogKind::Full {
             out |= SubtaskVisibleComponents::SCORE;
         }
+        if let GroupVisPreset::Custom { score: true, .. } = self {
+            out |= SubtaskVisibleComponents::SCORE;
+        }
         out
     }
 
@@ -57,12 +103,34 @@ impl GroupVisPreset {
         if self == GroupVisPreset::Brief {
             out |= TestVisibleComponents::STATUS | TestVisibleComponents::RESOURCE_USAGE;
         }
+        if let GroupVisPreset::Custom { tests, .. } = self {
+            out |= tests;
+        }
         out
     }
 
+    /// Components which are additionally visible for the first failed test
+    fn first_failed_test_flags_for(self, k: JudgeLogKind) -> TestVisibleComponents {
+        match self {
+            GroupVisPreset::Custom {
+                first_failed_test, ..
+            } if k == JudgeLogKind::Contestant => first_failed_test,
+            _ => TestVisibleComponents::empty(),
+        }
+    }
+
+    fn shown_tests_for(self, k: JudgeLogKind) -> ShownTests {
+        match self {
+            GroupVisPreset::Custom { shown_tests, .. } if k == JudgeLogKind::Contestant => {
+                shown_tests
+            }
+            _ => ShownTests::All,
+        }
+    }
+
     fn is_visible_for(self, k: JudgeLogKind) -> bool {
         match self {
-            GroupVisPreset::Brief | GroupVisPreset::Full => true,
+            GroupVisPreset::Brief | GroupVisPreset::Full | GroupVisPreset::Custom { .. } => true,
             GroupVisPreset::Hidden => k == JudgeLogKind::Full,
         }
     }
@@ -73,40 +141,76 @@ impl Fiber {
         let mut groups = Vec::new();
         let mut visible_tests = HashSet::new();
         let mut skipped_groups = HashSet::new();
+        let mut has_closed_tests = false;
         for (i, group_cfg) in cfg.groups.iter().enumerate() {
-            let vis_preset = match group_cfg.feedback {
-                crate::cfg::FeedbackKind::Brief => GroupVisPreset::Brief,
-                crate::cfg::FeedbackKind::Full => GroupVisPreset::Full,
-                crate::cfg::FeedbackKind::Hidden => GroupVisPreset::Hidden,
+            let mut tests = Vec::new();
+            if !group_cfg.skip {
+                for i in 0..problem_info.tests.len() {
+                    if problem_info.test_has_tag(i, group_cfg.tests_tag()) {
+                        tests.push((i + 1) as u32);
+                    }
+                }
+            }
+            let open_tests_count = tests.iter().filter(|&&t| cfg.is_test_open(t)).count();
+            let vis_preset = if cfg.is_open(i) && (open_tests_count != 0 || tests.is_empty()) {
+                GroupVisPreset::from_feedback(&group_cfg.feedback)
+            } else {
+                GroupVisPreset::Hidden
             };
             if !vis_preset.is_visible_for(kind) {
                 skipped_groups.insert(i);
                 continue;
             }
+            if kind == JudgeLogKind::Contestant && open_tests_count != tests.len() {
+                // closed tests are only valued in full judge log
+                tests.retain(|&t| cfg.is_test_open(t));
+                has_closed_tests = true;
+            }
             let mut grp = Group::new();
-            grp.set_id(NonZeroU32::new((i + 1) as u32).unwrap());
-            let mut tests = Vec::new();
-            for (i, test_tag) in problem_info.tests.iter().enumerate() {
-                if test_tag == group_cfg.tests_tag() {
-                    tests.push((i + 1) as u32);
-                }
+            grp.set_id(NonZeroU32::new((i + 1) as u32).unwrap())
+                .set_name(&group_cfg.name);
+            grp.set_tests_vis(vis_preset.test_flags_for(kind))
+                .set_first_failed_test_vis(vis_preset.first_failed_test_flags_for(kind))
+                .set_shown_tests(vis_preset.shown_tests_for(kind))
+                .set_group_vis(vis_preset.subtask_flags_for(kind));
+            if group_cfg.skip {
+                // group without tests passes immediately with zero score
+                grp.set_not_run();
+                grp.freeze();
+                groups.push(grp);
+                continue;
             }
             visible_tests.extend(tests.iter().map(|test_id| pom::TestId::make(*test_id)));
             grp.add_tests(tests);
-
-            grp.set_tests_vis(vis_preset.test_flags_for(kind))
-                .set_group_vis(vis_preset.subtask_flags_for(kind));
             grp.set_score(group_cfg.score);
+            if group_cfg.scoring == crate::cfg::ScoringPolicy::Partial {
+                grp.set_partial();
+            }
+            if let Some(test_score) = group_cfg.test_score {
+                grp.set_test_score(test_score);
+            }
+            if let Some(count) = group_cfg.pass_if_count {
+                grp.set_pass_if_count(count);
+            }
+            if group_cfg.zero_if_failed {
+                grp.set_zero_if_failed();
+            }
+            if let Some(policy) = group_cfg.fraction_policy {
+                grp.set_fraction_policy(policy);
+            }
             for dep in &group_cfg.deps {
                 let group_id = cfg.get_group(dep).expect("invalid config");
                 if skipped_groups.contains(&group_id) {
                     continue;
                 }
-                grp.add_dep(group_id as u32);
+                grp.add_dep(SubtaskId::make(group_id as u32 + 1));
             }
             if !group_cfg.run_to_first_failure {
                 grp.set_run_all_tests();
             }
+            if cfg.max_parallel_tests.is_some() {
+                grp.set_parallel();
+            }
 
             grp.freeze();
 
@@ -117,6 +221,7 @@ impl Fiber {
             visible_tests,
             active_groups: (0..groups.len()).collect(),
             finished: false,
+            has_hidden_tests: !skipped_groups.is_empty() || has_closed_tests,
             groups,
             last_live_score: 0,
         }
@@ -129,27 +234,96 @@ impl Fiber {
         if self.finished {
             panic!("Fiber is finished, but got notification {:?}", notification);
         }
-        self.add_test(notification.test_id, &notification.test_status);
+        tracing::debug!(
+            "processing status {:?} for test {}",
+            notification.test_status,
+            notification.test_id
+        );
+        for g in &mut self.groups {
+            g.on_test_done(notification);
+        }
     }
 
     pub(crate) fn kind(&self) -> JudgeLogKind {
         self.kind
     }
 
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Checks if result of the test can affect this fiber
+    pub(crate) fn needs_test(&self, test_id: TestId) -> bool {
+        !self.finished && self.groups.iter().any(|g| g.needs_test(test_id))
+    }
+
+    /// Returns tests which are likely to be requested later
+    pub(crate) fn speculative_tests(&self) -> impl Iterator<Item = TestId> + '_ {
+        let groups = if self.finished {
+            &[][..]
+        } else {
+            &self.groups[..]
+        };
+        groups.iter().flat_map(|g| g.pending_tests())
+    }
+
     fn emit_judgelog(&mut self) -> FiberReply {
         tracing::info!("Emitting {:?} judge log", self.kind);
-        let is_full = self.groups.iter().all(|g| g.is_passed());
+        let is_full = !self.has_hidden_tests
+            && !self.groups.is_empty()
+            && self.groups.iter().all(|g| g.is_passed());
         let mut judge_log = JudgeLog {
             kind: self.kind,
-            tests: vec![],
-            subtasks: vec![],
             is_full,
-            score: 0,
+            ..Default::default()
         };
         for (i, g) in self.groups.iter().enumerate() {
             tracing::debug!("extending judge log with group {}", i);
             g.update_judge_log(&mut judge_log);
         }
+        if self.groups.iter().any(|g| g.zeroes_total()) {
+            judge_log.score = 0;
+        }
+        // test shared by several groups is reported once, with union of its components
+        let mut tests: Vec<JudgeLogTestRow> = Vec::new();
+        // maps test to its position in `tests`
+        let mut positions: BTreeMap<TestId, usize> = BTreeMap::new();
+        for row in std::mem::take(&mut judge_log.tests) {
+            match positions.get(&row.test_id) {
+                Some(&pos) => {
+                    let prev = &mut tests[pos];
+                    prev.components |= row.components;
+                    prev.resource_usage = prev.resource_usage.or(row.resource_usage);
+                }
+                None => {
+                    positions.insert(row.test_id, tests.len());
+                    tests.push(row);
+                }
+            }
+        }
+        judge_log.tests = tests;
+        let first_failed = judge_log
+            .tests
+            .iter()
+            .filter(|row| row.components.contains(TestVisibleComponents::STATUS))
+            .filter(|row| !row.status.kind.is_success())
+            .min_by_key(|row| row.test_id);
+        judge_log.failed_test = first_failed.map(|row| row.test_id);
+        judge_log.max_resource_usage =
+            ResourceUsage::max_of(judge_log.tests.iter().filter_map(|row| row.resource_usage));
+        judge_log.status = if is_full {
+            crate::status_util::make_accepted_status()
+        } else if judge_log.score > 0 {
+            crate::status_util::make_partial_status()
+        } else {
+            match first_failed {
+                Some(row) => row.status.clone(),
+                // outcome of hidden groups is unknown to this judge log
+                None if self.has_hidden_tests => Status::default(),
+                // e.g. status of failed test is hidden
+                None => crate::status_util::make_rejected_status(),
+            }
+        };
 
         FiberReply::Finish(judge_log)
     }
@@ -171,6 +345,9 @@ impl Fiber {
     }
 
     fn current_score(&self) -> u32 {
+        if self.groups.iter().any(|g| g.zeroes_total()) {
+            return 0;
+        }
         self.groups.iter().map(|g| g.score()).sum()
     }
 
@@ -208,22 +385,23 @@ impl Fiber {
                 new_active_groups.push(i);
             }
             assert!(!(is_passed && is_failed));
+            let id = g.id();
             if g.is_passed() {
                 tracing::debug!("group {} is passed", i);
                 for group in &mut self.groups {
-                    group.on_group_pass(i as u32);
+                    group.on_group_pass(id);
                 }
             } else if g.is_failed() {
-                let mut queue = vec![i as u32];
+                let mut queue = vec![id];
                 while let Some(k) = queue.pop() {
-                    tracing::debug!("group {} is failed", k);
-                    for (j, group) in self.groups.iter_mut().enumerate() {
+                    tracing::debug!("group {:?} is failed", k);
+                    for group in &mut self.groups {
                         if !group.is_waiting() {
                             continue;
                         }
-                        group.on_group_fail(k as u32);
+                        group.on_group_fail(k);
                         if group.is_skipped() {
-                            queue.push(j as u32);
+                            queue.push(group.id());
                         }
                     }
                 }
@@ -257,24 +435,13 @@ impl Fiber {
             FiberReply::None
         }
     }
-
-    fn add_test(&mut self, test: TestId, status: &Status) {
-        tracing::debug!("processing status {:?} for test {}", status, test);
-        if !self.visible_tests.contains(&test) {
-            tracing::debug!("skipping: test is not visible");
-            return;
-        }
-        for g in &mut self.groups {
-            g.on_test_done(test, status.clone());
-        }
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use valuer_api::{
-        JudgeLogSubtaskRow, JudgeLogTestRow, SubtaskId, SubtaskVisibleComponents,
+        JudgeLogSubtaskRow, JudgeLogTestRow, SubtaskId, SubtaskState, SubtaskVisibleComponents,
         TestVisibleComponents,
     };
 
@@ -283,6 +450,7 @@ mod tests {
             &serde_yaml::from_str(cfg).unwrap(),
             &ProblemInfo {
                 tests: problem_info.iter().map(ToString::to_string).collect(),
+                test_tags: Vec::new(),
             },
             kind,
         )
@@ -317,7 +485,10 @@ groups:
             }
         );
         assert_eq!(f.poll(), FiberReply::None);
-        f.add_test(TestId::make(1), &crate::status_util::make_ok_status());
+        f.add(&crate::make_notification(
+            1,
+            crate::status_util::make_ok_status(),
+        ));
         assert_eq!(
             f.poll(),
             FiberReply::Test {
@@ -325,7 +496,10 @@ groups:
             }
         );
         assert_eq!(f.poll(), FiberReply::None);
-        f.add_test(TestId::make(2), &crate::status_util::make_ok_status());
+        f.add(&crate::make_notification(
+            2,
+            crate::status_util::make_ok_status(),
+        ));
         assert_eq!(f.poll(), FiberReply::LiveScore { score: 60 });
         assert_eq!(
             f.poll(),
@@ -334,7 +508,10 @@ groups:
             }
         );
         assert_eq!(f.poll(), FiberReply::None);
-        f.add_test(TestId::make(3), &crate::status_util::make_err_status());
+        f.add(&crate::make_notification(
+            3,
+            crate::status_util::make_err_status(),
+        ));
         let mut judge_log = match f.poll() {
             FiberReply::Finish(log) => log,
             oth => panic!("{:?}", oth),
@@ -345,17 +522,20 @@ groups:
                 JudgeLogTestRow {
                     test_id: TestId::make(1),
                     status: crate::status_util::make_ok_status(),
-                    components: TestVisibleComponents::all()
+                    components: TestVisibleComponents::all(),
+                    resource_usage: None,
                 },
                 JudgeLogTestRow {
                     test_id: TestId::make(2),
                     status: crate::status_util::make_ok_status(),
-                    components: TestVisibleComponents::all()
+                    components: TestVisibleComponents::all(),
+                    resource_usage: None,
                 },
                 JudgeLogTestRow {
                     test_id: TestId::make(3),
                     status: crate::status_util::make_err_status(),
-                    components: TestVisibleComponents::all()
+                    components: TestVisibleComponents::all(),
+                    resource_usage: None,
                 },
             ],
         );
@@ -364,18 +544,33 @@ groups:
             vec![
                 JudgeLogSubtaskRow {
                     subtask_id: SubtaskId::make(1),
+                    name: "samples".to_string(),
+                    state: SubtaskState::Passed,
+                    skipped_because: None,
+                    failed_test: None,
                     score: 0,
-                    components: SubtaskVisibleComponents::all()
+                    components: SubtaskVisibleComponents::all(),
+                    max_resource_usage: None,
                 },
                 JudgeLogSubtaskRow {
                     subtask_id: SubtaskId::make(2),
+                    name: "online".to_string(),
+                    state: SubtaskState::Passed,
+                    skipped_because: None,
+                    failed_test: None,
                     score: 60,
-                    components: SubtaskVisibleComponents::all()
+                    components: SubtaskVisibleComponents::all(),
+                    max_resource_usage: None,
                 },
                 JudgeLogSubtaskRow {
                     subtask_id: SubtaskId::make(3),
+                    name: "offline".to_string(),
+                    state: SubtaskState::Failed,
+                    skipped_because: None,
+                    failed_test: Some(TestId::make(3)),
                     score: 0,
-                    components: SubtaskVisibleComponents::all()
+                    components: SubtaskVisibleComponents::all(),
+                    max_resource_usage: None,
                 }
             ]
         );
@@ -386,8 +581,218 @@ groups:
                 kind: JudgeLogKind::Full,
                 score: 60,
                 tests: vec![],
-                subtasks: vec![]
+                subtasks: vec![],
+                status: crate::status_util::make_partial_status(),
+                failed_test: Some(TestId::make(3)),
+                max_resource_usage: None,
             }
         );
     }
+
+    #[test]
+    fn skip_and_zero_if_failed() {
+        crate::setup_log();
+        let mut f = make_fiber(
+            "
+groups:
+  - name: samples
+    feedback: full
+    score: 0
+    skip: true
+  - name: first
+    feedback: brief
+    score: 30
+    deps:
+      - samples
+  - name: second
+    feedback: brief
+    score: 70
+    zero_if_failed: true
+        ",
+            &["samples", "first", "second"],
+            JudgeLogKind::Full,
+        );
+        // skipped group does not block `first`
+        assert_eq!(
+            f.poll(),
+            FiberReply::Test {
+                test_id: TestId::make(2)
+            }
+        );
+        assert_eq!(
+            f.poll(),
+            FiberReply::Test {
+                test_id: TestId::make(3)
+            }
+        );
+        f.add(&crate::make_notification(
+            2,
+            crate::status_util::make_ok_status(),
+        ));
+        assert_eq!(f.poll(), FiberReply::LiveScore { score: 30 });
+        f.add(&crate::make_notification(
+            3,
+            crate::status_util::make_err_status(),
+        ));
+        assert_eq!(f.poll(), FiberReply::LiveScore { score: 0 });
+        let judge_log = match f.poll() {
+            FiberReply::Finish(log) => log,
+            oth => panic!("{:?}", oth),
+        };
+        assert_eq!(judge_log.score, 0);
+        let subtask_scores: Vec<_> = judge_log.subtasks.iter().map(|s| s.score).collect();
+        assert_eq!(subtask_scores, vec![0, 30, 0]);
+    }
+
+    #[test]
+    fn custom_feedback() {
+        crate::setup_log();
+        let mut f = make_fiber(
+            "
+groups:
+  - name: main
+    score: 100
+    run_to_first_failure: false
+    feedback:
+      custom:
+        tests: [status]
+        first_failed_test: [test-data]
+        shown_tests: first-failed
+        score: false
+        ",
+            &["main", "main", "main"],
+            JudgeLogKind::Contestant,
+        );
+        for i in 1..=3 {
+            assert_eq!(
+                f.poll(),
+                FiberReply::Test {
+                    test_id: TestId::make(i)
+                }
+            );
+        }
+        f.add(&crate::make_notification(
+            1,
+            crate::status_util::make_ok_status(),
+        ));
+        f.add(&crate::make_notification(
+            3,
+            crate::status_util::make_err_status(),
+        ));
+        f.add(&crate::make_notification(
+            2,
+            crate::status_util::make_err_status(),
+        ));
+        let judge_log = match f.poll() {
+            FiberReply::Finish(log) => log,
+            oth => panic!("{:?}", oth),
+        };
+        assert_eq!(
+            judge_log.tests,
+            vec![JudgeLogTestRow {
+                test_id: TestId::make(2),
+                status: crate::status_util::make_err_status(),
+                components: TestVisibleComponents::STATUS | TestVisibleComponents::TEST_DATA,
+                resource_usage: None,
+            }]
+        );
+        assert_eq!(
+            judge_log.subtasks[0].components,
+            SubtaskVisibleComponents::empty()
+        );
+        assert_eq!(judge_log.failed_test, Some(TestId::make(2)));
+    }
+
+    #[test]
+    fn subtask_states() {
+        crate::setup_log();
+        let mut f = make_fiber(
+            "
+groups:
+  - name: hidden
+    feedback: hidden
+    score: 0
+  - name: first
+    feedback: brief
+    score: 50
+  - name: second
+    feedback: brief
+    score: 50
+    deps:
+      - first
+  - name: third
+    feedback: brief
+    score: 0
+    deps:
+      - second
+        ",
+            &["hidden", "first", "second", "third"],
+            JudgeLogKind::Contestant,
+        );
+        assert_eq!(
+            f.poll(),
+            FiberReply::Test {
+                test_id: TestId::make(2)
+            }
+        );
+        f.add(&crate::make_notification(
+            2,
+            crate::status_util::make_ok_status(),
+        ));
+        assert_eq!(f.poll(), FiberReply::LiveScore { score: 50 });
+        // dependency is resolved even though hidden group is not in this fiber
+        assert_eq!(
+            f.poll(),
+            FiberReply::Test {
+                test_id: TestId::make(3)
+            }
+        );
+        f.add(&crate::make_notification(
+            3,
+            crate::status_util::make_err_status(),
+        ));
+        let judge_log = match f.poll() {
+            FiberReply::Finish(log) => log,
+            oth => panic!("{:?}", oth),
+        };
+        let states: Vec<_> = judge_log
+            .subtasks
+            .iter()
+            .map(|row| {
+                (
+                    row.subtask_id,
+                    row.name.as_str(),
+                    row.state,
+                    row.skipped_because,
+                    row.failed_test,
+                )
+            })
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (
+                    SubtaskId::make(2),
+                    "first",
+                    SubtaskState::Passed,
+                    None,
+                    None
+                ),
+                (
+                    SubtaskId::make(3),
+                    "second",
+                    SubtaskState::Failed,
+                    None,
+                    Some(TestId::make(3))
+                ),
+                (
+                    SubtaskId::make(4),
+                    "third",
+                    SubtaskState::Skipped,
+                    Some(SubtaskId::make(3)),
+                    None
+                ),
+            ]
+        );
+    }
 }
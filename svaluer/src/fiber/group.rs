@@ -1,37 +1,45 @@
+use crate::cfg::{FractionPolicy, ShownTests};
 use either::{Left, Right};
 use pom::TestId;
-use std::collections::BTreeSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use valuer_api::{
-    JudgeLog, JudgeLogSubtaskRow, JudgeLogTestRow, Status, SubtaskId, SubtaskVisibleComponents,
-    TestVisibleComponents,
+    JudgeLog, JudgeLogSubtaskRow, JudgeLogTestRow, ResourceUsage, Status, SubtaskId, SubtaskState,
+    SubtaskVisibleComponents, TestDoneNotification, TestVisibleComponents,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct RunningState {
     queued_tests: BTreeSet<TestId>,
     succeeded_tests: BTreeSet<(TestId, Status)>,
     failed_tests: BTreeSet<(TestId, Status)>,
     running_tests: BTreeSet<TestId>,
+    /// Share of test score gained on each finished test
+    shares: Vec<f64>,
+    resource_usage: BTreeMap<TestId, ResourceUsage>,
+    /// Results which arrived while some test with smaller id is still running
+    early_results: BTreeMap<TestId, TestDoneNotification>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct WaitingState {
     deps: BTreeSet<u32>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct SkippedState {
-    failed_dep: u32,
+    failed_dep: SubtaskId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct FinishedState {
     score: u32,
     success: bool,
     tests: Vec<(TestId, Status)>,
+    resource_usage: BTreeMap<TestId, ResourceUsage>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 enum State {
     Building,
     Running(RunningState),
@@ -40,29 +48,57 @@ enum State {
     Finished(FinishedState),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct Group {
     id: SubtaskId,
+    name: String,
+    /// Group is disabled in config
+    not_run: bool,
     dep_groups: Vec<u32>,
     test_vis_flags: TestVisibleComponents,
+    /// Added to `test_vis_flags` for the first failed test
+    first_failed_test_vis_flags: TestVisibleComponents,
+    shown_tests: ShownTests,
     subtask_vis_flags: SubtaskVisibleComponents,
     run_all_tests: bool,
+    /// Start next tests without waiting for running ones, even if group stops
+    /// on first failure
+    parallel: bool,
     state: State,
     tests: Vec<TestId>,
     score: u32,
+    /// Give part of `score` proportional to passed tests
+    partial: bool,
+    /// Score for each passed test
+    test_score: u32,
+    /// If set, group is passed when this number of tests passed
+    pass_if_count: Option<u32>,
+    zero_if_failed: bool,
+    /// If set, each test gives `test_score` scaled by its share
+    fraction_policy: Option<FractionPolicy>,
 }
 
 impl Group {
     pub(crate) fn new() -> Group {
         Group {
             id: SubtaskId(std::num::NonZeroU32::new(u32::max_value()).unwrap()),
+            name: String::new(),
+            not_run: false,
             dep_groups: Vec::new(),
             test_vis_flags: TestVisibleComponents::empty(),
+            first_failed_test_vis_flags: TestVisibleComponents::empty(),
+            shown_tests: ShownTests::All,
             subtask_vis_flags: SubtaskVisibleComponents::empty(),
             run_all_tests: false,
+            parallel: false,
             state: State::Building,
             tests: Vec::new(),
             score: 0,
+            partial: false,
+            test_score: 0,
+            pass_if_count: None,
+            zero_if_failed: false,
+            fraction_policy: None,
         }
     }
 
@@ -76,15 +112,28 @@ impl Group {
         self
     }
 
+    pub(crate) fn set_name(&mut self, name: &str) -> &mut Self {
+        self.check_mutable();
+        self.name = name.to_string();
+        self
+    }
+
+    /// Marks group as disabled. Such group has no tests and passes immediately.
+    pub(crate) fn set_not_run(&mut self) -> &mut Self {
+        self.check_mutable();
+        self.not_run = true;
+        self
+    }
+
     pub(crate) fn add_tests(&mut self, range: impl IntoIterator<Item = u32>) -> &mut Self {
         self.check_mutable();
         self.tests.extend(range.into_iter().map(TestId::make)); // TODO: do not panic
         self
     }
 
-    pub(crate) fn add_dep(&mut self, dep_id: u32) -> &mut Self {
+    pub(crate) fn add_dep(&mut self, dep_id: SubtaskId) -> &mut Self {
         self.check_mutable();
-        self.dep_groups.push(dep_id);
+        self.dep_groups.push(dep_id.0.get());
         self
     }
 
@@ -94,12 +143,60 @@ impl Group {
         self
     }
 
+    pub(crate) fn set_partial(&mut self) -> &mut Self {
+        self.check_mutable();
+        self.partial = true;
+        // every test affects score
+        self.run_all_tests = true;
+        self
+    }
+
+    pub(crate) fn set_test_score(&mut self, test_score: u32) -> &mut Self {
+        self.check_mutable();
+        self.test_score = test_score;
+        // every test affects score
+        self.run_all_tests = true;
+        self
+    }
+
+    pub(crate) fn set_pass_if_count(&mut self, count: u32) -> &mut Self {
+        self.check_mutable();
+        self.pass_if_count = Some(count);
+        self
+    }
+
+    pub(crate) fn set_zero_if_failed(&mut self) -> &mut Self {
+        self.check_mutable();
+        self.zero_if_failed = true;
+        self
+    }
+
+    pub(crate) fn set_fraction_policy(&mut self, policy: FractionPolicy) -> &mut Self {
+        self.check_mutable();
+        self.fraction_policy = Some(policy);
+        // every test affects score
+        self.run_all_tests = true;
+        self
+    }
+
     pub(crate) fn set_tests_vis(&mut self, vis: TestVisibleComponents) -> &mut Self {
         self.check_mutable();
         self.test_vis_flags = vis;
         self
     }
 
+    pub(crate) fn set_first_failed_test_vis(&mut self, vis: TestVisibleComponents) -> &mut Self {
+        self.check_mutable();
+        self.first_failed_test_vis_flags = vis;
+        self
+    }
+
+    pub(crate) fn set_shown_tests(&mut self, shown_tests: ShownTests) -> &mut Self {
+        self.check_mutable();
+        self.shown_tests = shown_tests;
+        self
+    }
+
     pub(crate) fn set_group_vis(&mut self, vis: SubtaskVisibleComponents) -> &mut Self {
         self.check_mutable();
         self.subtask_vis_flags = vis;
@@ -112,6 +209,12 @@ impl Group {
         self
     }
 
+    pub(crate) fn set_parallel(&mut self) -> &mut Self {
+        self.check_mutable();
+        self.parallel = true;
+        self
+    }
+
     pub(crate) fn freeze(&mut self) {
         self.state = State::Waiting(WaitingState {
             deps: self.dep_groups.iter().copied().collect(),
@@ -120,6 +223,12 @@ impl Group {
     }
 }
 
+/// Multiplies score by share, rounding down.
+/// Small error is allowed, so that e.g. 0.29 * 100 is 29.
+fn scale(score: u32, share: f64) -> u32 {
+    (f64::from(score) * share + 1e-9).floor() as u32
+}
+
 impl Group {
     fn finished(&self) -> Option<bool> {
         match &self.state {
@@ -136,6 +245,10 @@ impl Group {
         self.finished() == Some(false)
     }
 
+    pub(crate) fn id(&self) -> SubtaskId {
+        self.id
+    }
+
     pub(crate) fn is_skipped(&self) -> bool {
         matches!(self.state, State::Skipped(_))
     }
@@ -144,6 +257,19 @@ impl Group {
         matches!(self.state, State::Waiting(_))
     }
 
+    /// Returns true if this group makes total score zero
+    pub(crate) fn zeroes_total(&self) -> bool {
+        self.zero_if_failed && (self.is_failed() || self.is_skipped())
+    }
+
+    /// How many tests may fail without failing the group
+    fn allowed_failures(&self) -> usize {
+        match self.pass_if_count {
+            Some(count) => self.tests.len().saturating_sub(count as usize),
+            None => 0,
+        }
+    }
+
     pub(crate) fn running_tests(&self) -> u32 {
         match &self.state {
             State::Running(state) => state.running_tests.len() as u32,
@@ -151,6 +277,26 @@ impl Group {
         }
     }
 
+    /// Checks if result of the test can affect this group
+    pub(crate) fn needs_test(&self, test_id: TestId) -> bool {
+        match &self.state {
+            State::Waiting(_) => self.tests.contains(&test_id),
+            State::Running(state) => {
+                state.running_tests.contains(&test_id) || state.queued_tests.contains(&test_id)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns tests which are not started yet, but may be requested later
+    pub(crate) fn pending_tests(&self) -> impl Iterator<Item = TestId> + '_ {
+        match &self.state {
+            State::Waiting(_) => Left(Left(self.tests.iter().copied())),
+            State::Running(state) => Left(Right(state.queued_tests.iter().copied())),
+            _ => Right(std::iter::empty()),
+        }
+    }
+
     pub(crate) fn list_running_tests(&self) -> impl Iterator<Item = TestId> + '_ {
         match &self.state {
             State::Running(state) => Left(state.running_tests.iter().copied()),
@@ -169,31 +315,36 @@ impl Group {
                 failed_tests: BTreeSet::new(),
                 succeeded_tests: BTreeSet::new(),
                 running_tests: BTreeSet::new(),
+                shares: Vec::new(),
+                resource_usage: BTreeMap::new(),
+                early_results: BTreeMap::new(),
             });
+            // group without tests is finished immediately
+            self.maybe_finish();
         }
     }
 
-    pub(crate) fn on_group_pass(&mut self, other_group_id: u32) {
+    pub(crate) fn on_group_pass(&mut self, other_group_id: SubtaskId) {
         let state = match &mut self.state {
             State::Waiting(state) => state,
             _ => return,
         };
-        if state.deps.remove(&other_group_id) {
-            tracing::debug!("group {:?}: dep {} passed", self.id, other_group_id);
+        if state.deps.remove(&other_group_id.0.get()) {
+            tracing::debug!("group {:?}: dep {:?} passed", self.id, other_group_id);
             self.maybe_stop_waiting();
         }
     }
 
-    pub(crate) fn on_group_fail(&mut self, other_group_id: u32) {
+    pub(crate) fn on_group_fail(&mut self, other_group_id: SubtaskId) {
         let state = match &mut self.state {
             State::Waiting(state) => state,
             _ => return,
         };
-        if !state.deps.contains(&other_group_id) {
+        if !state.deps.contains(&other_group_id.0.get()) {
             // that group was not required, so we ignore this failure
             return;
         }
-        tracing::debug!("group {:?}: dep {} failed", self.id, other_group_id);
+        tracing::debug!("group {:?}: dep {:?} failed", self.id, other_group_id);
         self.state = State::Skipped(SkippedState {
             failed_dep: other_group_id,
         });
@@ -215,7 +366,7 @@ impl Group {
             }
             State::Running(state) => state,
         };
-        if !state.running_tests.is_empty() && !self.run_all_tests {
+        if !state.running_tests.is_empty() && !self.run_all_tests && !self.parallel {
             tracing::debug!("Returning None: run_all_tests=false, and a test is already running");
             return None;
         }
@@ -240,14 +391,19 @@ impl Group {
     fn mark_test_fail(&mut self, test_id: TestId, status: Status) {
         let id = self.id;
         let must_run_all_tests = self.run_all_tests;
+        let allowed_failures = self.allowed_failures();
         let state = self.running_state();
-        if state.failed_tests.is_empty() {
-            tracing::debug!("group {:?} is now failed", id);
-        }
         state.failed_tests.insert((test_id, status));
+        if state.failed_tests.len() <= allowed_failures {
+            return;
+        }
+        tracing::debug!("group {:?} is now failed", id);
         if !must_run_all_tests {
-            // no other tests should be run
+            // no other tests should be run, and results of tests after this
+            // one are discarded
             state.queued_tests.clear();
+            state.running_tests.clear();
+            state.early_results.clear();
         }
     }
 
@@ -258,49 +414,121 @@ impl Group {
     }
 
     fn maybe_finish(&mut self) {
+        let group_score = self.score;
+        let partial = self.partial;
+        let tests_count = self.tests.len() as u64;
+        let test_score = self.test_score;
+        let pass_if_count = self.pass_if_count;
+        let fraction_policy = self.fraction_policy;
         let state = self.running_state();
         if state.queued_tests.is_empty() && state.running_tests.is_empty() {
-            let success = state.failed_tests.is_empty();
+            let passed_count = state.succeeded_tests.len() as u32;
+            let success = match pass_if_count {
+                Some(count) => passed_count >= count,
+                None => state.failed_tests.is_empty(),
+            };
             let failed_tests = std::mem::take(&mut state.failed_tests);
             let succeeded_tests = std::mem::take(&mut state.succeeded_tests);
-            let score = if success { self.score } else { 0 };
+            let shares = std::mem::take(&mut state.shares);
+            let resource_usage = std::mem::take(&mut state.resource_usage);
+            let mut score = match fraction_policy {
+                Some(_) => scale(test_score, shares.iter().sum()),
+                None => passed_count * test_score,
+            };
+            match fraction_policy {
+                Some(FractionPolicy::Min) => {
+                    let min = shares.iter().copied().fold(1.0, f64::min);
+                    score += scale(group_score, min);
+                }
+                Some(FractionPolicy::Average) => {
+                    let average = if shares.is_empty() {
+                        1.0
+                    } else {
+                        shares.iter().sum::<f64>() / shares.len() as f64
+                    };
+                    score += scale(group_score, average);
+                }
+                _ if partial && tests_count != 0 => {
+                    score +=
+                        (u64::from(group_score) * u64::from(passed_count) / tests_count) as u32;
+                }
+                _ if success => score += group_score,
+                _ => (),
+            }
             self.state = State::Finished(FinishedState {
                 score,
                 success,
                 tests: failed_tests.into_iter().chain(succeeded_tests).collect(),
+                resource_usage,
             })
         }
     }
 
-    pub(crate) fn on_test_done(&mut self, test_id: TestId, status: Status) {
+    pub(crate) fn on_test_done(&mut self, notification: &TestDoneNotification) {
+        let test_id = notification.test_id;
         let state = match &mut self.state {
             State::Running(state) => state,
             _ => return,
         };
-        if !state.running_tests.remove(&test_id) {
+        if !state.running_tests.contains(&test_id) {
             return;
         }
         tracing::debug!(
             "got test result: test={}, status={:?}",
             test_id.get(),
-            status
+            notification.test_status
         );
+        state.early_results.insert(test_id, notification.clone());
+        // results are applied in test order, so that running tests in
+        // parallel gives the same outcome as running them one by one
+        while let State::Running(state) = &mut self.state {
+            let next = match state.running_tests.iter().next() {
+                Some(&test_id) => test_id,
+                None => break,
+            };
+            let notification = match state.early_results.remove(&next) {
+                Some(notification) => notification,
+                None => break,
+            };
+            state.running_tests.remove(&next);
+            self.apply_test_result(notification);
+        }
+        self.maybe_finish();
+    }
+
+    fn apply_test_result(&mut self, notification: TestDoneNotification) {
+        let test_id = notification.test_id;
+        let status = notification.test_status;
+        let state = self.running_state();
+        if let Some(usage) = notification.resource_usage {
+            state.resource_usage.insert(test_id, usage);
+        }
+        let share = match notification.fraction {
+            Some(fraction) => fraction.share(),
+            None if status.kind.is_success() => 1.0,
+            None => 0.0,
+        };
+        state.shares.push(share);
         if status.kind.is_success() {
             self.mark_test_ok(test_id, status);
         } else {
             self.mark_test_fail(test_id, status);
         }
-        self.maybe_finish();
     }
 
     pub(crate) fn update_judge_log(&self, log: &mut JudgeLog) {
         let state = match &self.state {
             State::Finished(state) => state,
-            State::Skipped(_) => {
+            State::Skipped(state) => {
                 log.subtasks.push(JudgeLogSubtaskRow {
                     components: self.subtask_vis_flags,
                     score: 0,
                     subtask_id: self.id,
+                    name: self.name.clone(),
+                    state: SubtaskState::Skipped,
+                    skipped_because: Some(state.failed_dep),
+                    failed_test: None,
+                    max_resource_usage: None,
                 });
                 return;
             }
@@ -308,27 +536,69 @@ impl Group {
         };
         let self_score = self.score();
         log.score += self_score;
+        let first_failed = state
+            .tests
+            .iter()
+            .filter(|(_, status)| !status.kind.is_success())
+            .map(|(test, _)| *test)
+            .min();
+        let rows: Vec<_> = state
+            .tests
+            .iter()
+            .filter(|(test, _)| match self.shown_tests {
+                ShownTests::All => true,
+                ShownTests::FirstFailed => Some(*test) == first_failed,
+                ShownTests::None => false,
+            })
+            .map(|(test, status)| {
+                let mut components = self.test_vis_flags;
+                if Some(*test) == first_failed {
+                    components |= self.first_failed_test_vis_flags;
+                }
+                JudgeLogTestRow {
+                    components,
+                    test_id: *test,
+                    status: status.clone(),
+                    resource_usage: if components.contains(TestVisibleComponents::RESOURCE_USAGE) {
+                        state.resource_usage.get(test).copied()
+                    } else {
+                        None
+                    },
+                }
+            })
+            .collect();
+        let subtask_state = if self.not_run {
+            SubtaskState::NotRun
+        } else if state.success {
+            SubtaskState::Passed
+        } else {
+            SubtaskState::Failed
+        };
         let subtask_entry = JudgeLogSubtaskRow {
             components: self.subtask_vis_flags,
             score: self_score,
             subtask_id: self.id,
+            name: self.name.clone(),
+            state: subtask_state,
+            skipped_because: None,
+            // failed test is reported only if its status is visible
+            failed_test: first_failed.filter(|test| {
+                rows.iter().any(|row| {
+                    row.test_id == *test && row.components.contains(TestVisibleComponents::STATUS)
+                })
+            }),
+            max_resource_usage: ResourceUsage::max_of(
+                rows.iter().filter_map(|row| row.resource_usage),
+            ),
         };
         log.subtasks.push(subtask_entry);
-        for (test, status) in &state.tests {
-            let row = JudgeLogTestRow {
-                components: self.test_vis_flags,
-                test_id: *test,
-                status: status.clone(),
-            };
-            log.tests.push(row);
-        }
+        log.tests.extend(rows);
     }
 
     pub(crate) fn score(&self) -> u32 {
-        if self.is_passed() {
-            self.score
-        } else {
-            0
+        match &self.state {
+            State::Finished(state) => state.score,
+            _ => 0,
         }
     }
 }
@@ -349,10 +619,170 @@ mod tests {
         g.freeze();
 
         assert_eq!(g.pop_test(), Some(TestId::make(1)));
-        g.on_test_done(TestId::make(1), st());
+        g.on_test_done(&crate::make_notification(1, st()));
         assert_eq!(g.pop_test(), Some(TestId::make(2)));
-        g.on_test_done(TestId::make(2), st());
+        g.on_test_done(&crate::make_notification(2, st()));
         assert_eq!(g.pop_test(), Some(TestId::make(3)));
         assert_eq!(g.pop_test(), None);
     }
+
+    #[test]
+    fn test_score_and_pass_if_count() {
+        crate::setup_log();
+        let ok = || crate::status_util::make_ok_status();
+        let err = || crate::status_util::make_err_status();
+        let mut g = Group::new();
+        g.add_tests(1..=4)
+            .set_score(10)
+            .set_test_score(5)
+            .set_pass_if_count(3);
+        g.freeze();
+
+        assert_eq!(g.pop_test(), Some(TestId::make(1)));
+        g.on_test_done(&crate::make_notification(1, err()));
+        // one failure is allowed, so group continues
+        assert_eq!(g.pop_test(), Some(TestId::make(2)));
+        g.on_test_done(&crate::make_notification(2, ok()));
+        assert_eq!(g.pop_test(), Some(TestId::make(3)));
+        g.on_test_done(&crate::make_notification(3, ok()));
+        assert_eq!(g.pop_test(), Some(TestId::make(4)));
+        g.on_test_done(&crate::make_notification(4, ok()));
+        assert!(g.is_passed());
+        assert_eq!(g.score(), 3 * 5 + 10);
+    }
+
+    #[test]
+    fn pass_if_count_failed() {
+        crate::setup_log();
+        let ok = || crate::status_util::make_ok_status();
+        let err = || crate::status_util::make_err_status();
+        let mut g = Group::new();
+        g.add_tests(1..=3)
+            .set_score(10)
+            .set_test_score(1)
+            .set_pass_if_count(2);
+        g.freeze();
+
+        assert_eq!(g.pop_test(), Some(TestId::make(1)));
+        g.on_test_done(&crate::make_notification(1, ok()));
+        assert_eq!(g.pop_test(), Some(TestId::make(2)));
+        g.on_test_done(&crate::make_notification(2, err()));
+        assert_eq!(g.pop_test(), Some(TestId::make(3)));
+        g.on_test_done(&crate::make_notification(3, err()));
+        assert!(g.is_failed());
+        // passed tests are still scored
+        assert_eq!(g.score(), 1);
+    }
+
+    #[test]
+    fn partial_scoring() {
+        crate::setup_log();
+        let mut g = Group::new();
+        g.add_tests(1..=3).set_score(50).set_partial();
+        g.freeze();
+
+        for i in 1..=3 {
+            assert_eq!(g.pop_test(), Some(TestId::make(i)));
+        }
+        g.on_test_done(&crate::make_notification(
+            1,
+            crate::status_util::make_ok_status(),
+        ));
+        g.on_test_done(&crate::make_notification(
+            2,
+            crate::status_util::make_err_status(),
+        ));
+        g.on_test_done(&crate::make_notification(
+            3,
+            crate::status_util::make_ok_status(),
+        ));
+        assert!(g.is_failed());
+        assert_eq!(g.score(), 50 * 2 / 3);
+    }
+
+    #[test]
+    fn fraction_policies() {
+        crate::setup_log();
+        let ok = crate::status_util::make_ok_status;
+        let half = Some(valuer_api::TestFraction {
+            numerator: 1,
+            denominator: 2,
+        });
+        let run = |policy: FractionPolicy, last_status: Status| {
+            let mut g = Group::new();
+            g.add_tests(1..=3)
+                .set_score(60)
+                .set_test_score(10)
+                .set_fraction_policy(policy);
+            g.freeze();
+            for i in 1..=3 {
+                assert_eq!(g.pop_test(), Some(TestId::make(i)));
+            }
+            g.on_test_done(&crate::make_notification(1, ok()));
+            g.on_test_done(&TestDoneNotification {
+                fraction: half,
+                ..crate::make_notification(2, ok())
+            });
+            g.on_test_done(&TestDoneNotification {
+                fraction: if last_status.kind.is_success() {
+                    half
+                } else {
+                    None
+                },
+                ..crate::make_notification(3, last_status)
+            });
+            g.score()
+        };
+        let err = crate::status_util::make_err_status;
+        // shares are 1, 1/2 and 0
+        assert_eq!(run(FractionPolicy::Sum, err()), 15);
+        assert_eq!(run(FractionPolicy::Min, err()), 15);
+        assert_eq!(run(FractionPolicy::Average, err()), 15 + 30);
+        // shares are 1, 1/2 and 1/2
+        assert_eq!(run(FractionPolicy::Sum, ok()), 20 + 60);
+        assert_eq!(run(FractionPolicy::Min, ok()), 20 + 30);
+        assert_eq!(run(FractionPolicy::Average, ok()), 20 + 40);
+    }
+
+    #[test]
+    fn resource_usage() {
+        crate::setup_log();
+        let usage = |time, memory| ResourceUsage {
+            time: Some(time),
+            wall_time: None,
+            memory: Some(memory),
+            exit_code: Some(0),
+        };
+        let run = |vis: TestVisibleComponents| {
+            let mut g = Group::new();
+            g.add_tests(1..=2).set_tests_vis(vis);
+            g.freeze();
+            assert_eq!(g.pop_test(), Some(TestId::make(1)));
+            g.on_test_done(&TestDoneNotification {
+                resource_usage: Some(usage(1840, 100)),
+                ..crate::make_notification(1, crate::status_util::make_ok_status())
+            });
+            assert_eq!(g.pop_test(), Some(TestId::make(2)));
+            g.on_test_done(&TestDoneNotification {
+                resource_usage: Some(usage(10, 500)),
+                ..crate::make_notification(2, crate::status_util::make_ok_status())
+            });
+            let mut log = JudgeLog::default();
+            g.update_judge_log(&mut log);
+            log
+        };
+        let log = run(TestVisibleComponents::all());
+        assert_eq!(log.tests[0].resource_usage, Some(usage(1840, 100)));
+        assert_eq!(
+            log.subtasks[0].max_resource_usage,
+            Some(ResourceUsage {
+                exit_code: None,
+                ..usage(1840, 500)
+            })
+        );
+
+        let log = run(TestVisibleComponents::STATUS);
+        assert_eq!(log.tests[0].resource_usage, None);
+        assert_eq!(log.subtasks[0].max_resource_usage, None);
+    }
 }
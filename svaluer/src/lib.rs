@@ -5,6 +5,7 @@
 mod tests;
 
 pub mod cfg;
+pub mod explore;
 mod fiber;
 
 pub use cfg::Config;
@@ -12,7 +13,8 @@ pub use cfg::Config;
 use anyhow::{Context, Result};
 use fiber::{Fiber, FiberReply};
 use pom::TestId;
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use valuer_api::{JudgeLogKind, ProblemInfo, TestDoneNotification, ValuerResponse};
 /// SValuer is pure. Only `ValuerDriver` actually performs some IO, interacting with environment, such as JJS invoker.
 pub trait ValuerDriver: std::fmt::Debug {
@@ -22,20 +24,67 @@ pub trait ValuerDriver: std::fmt::Debug {
     fn send_command(&mut self, cmd: &ValuerResponse) -> Result<()>;
     /// Polls notification about test finish
     fn poll_notification(&mut self) -> Result<Option<TestDoneNotification>>;
+    /// Called each time valuer state changes.
+    /// Driver can store `state` to resume valuing after restart.
+    fn save_snapshot(&mut self, _state: &ValuerState) -> Result<()> {
+        Ok(())
+    }
 }
 
-/// SValuer itself
-#[derive(Debug)]
-pub struct SimpleValuer<'a> {
-    driver: &'a mut dyn ValuerDriver,
-    /// Amount of tests that are currently running.
-    running_tests: u32,
+/// Complete state of `SimpleValuer`.
+/// It can be serialized, and later used to resume valuing.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValuerState {
+    /// Tests that are currently running.
+    running_tests: HashSet<TestId>,
+    /// Tests that were requested, but wait until some running test finishes
+    /// (see `max_parallel_tests`).
+    queued_tests: BTreeSet<TestId>,
+    /// Tests that were cancelled while running.
+    /// Notifications about them are ignored.
+    cancelled_tests: HashSet<TestId>,
     /// How many fibers did not emit judge log yet
     running_fibers: usize,
     /// Amount of tests that were requested to run.
     /// It is used for caching purposes.
     used_tests: HashSet<TestId>,
+    /// Results of finished tests, replayed when test is requested again
+    /// (e.g. by another group it belongs to).
+    finished_tests: HashMap<TestId, TestDoneNotification>,
     fibers: Vec<Fiber>,
+    /// See `Config::max_parallel_tests`
+    max_parallel_tests: Option<u32>,
+}
+
+impl ValuerState {
+    fn test_is_live(&self, test_id: TestId) -> bool {
+        self.fibers.iter().any(|fib| fib.test_is_live(test_id))
+    }
+
+    /// Checks that one more test can be started without exceeding `max_parallel_tests`
+    fn has_free_slot(&self) -> bool {
+        match self.max_parallel_tests {
+            // at least one test must be able to run
+            Some(max) => self.running_tests.len() < max.max(1) as usize,
+            None => true,
+        }
+    }
+}
+
+/// SValuer itself
+#[derive(Debug)]
+pub struct SimpleValuer<'a> {
+    driver: &'a mut dyn ValuerDriver,
+    state: ValuerState,
+}
+
+/// Result of one iteration
+enum Step {
+    /// State was changed
+    Progress,
+    /// Nothing happened, valuer waits for notifications
+    Wait,
+    Done,
 }
 
 impl<'a> SimpleValuer<'a> {
@@ -53,23 +102,58 @@ impl<'a> SimpleValuer<'a> {
         ];
 
         let fibers_cnt = fibers.len();
-        Ok(SimpleValuer {
-            driver,
-            running_tests: 0,
+        let state = ValuerState {
+            running_tests: HashSet::new(),
+            queued_tests: BTreeSet::new(),
+            cancelled_tests: HashSet::new(),
             used_tests: HashSet::new(),
+            finished_tests: HashMap::new(),
             fibers,
             running_fibers: fibers_cnt,
-        })
+            max_parallel_tests: cfg.max_parallel_tests,
+        };
+        Ok(SimpleValuer { driver, state })
+    }
+
+    /// Continues valuing from `state` previously saved by `ValuerDriver::save_snapshot`.
+    /// Tests which were running are assumed to be lost, so they are requested again.
+    pub fn resume(
+        driver: &'a mut dyn ValuerDriver,
+        state: ValuerState,
+    ) -> anyhow::Result<SimpleValuer<'a>> {
+        // `cancelled_tests` are kept: cancelled test may still finish and
+        // its late notification must be ignored
+        let mut running_tests: Vec<_> = state.running_tests.iter().copied().collect();
+        running_tests.sort();
+        for test_id in running_tests {
+            let live = state.test_is_live(test_id);
+            driver
+                .send_command(&ValuerResponse::Test { test_id, live })
+                .context("failed to send TEST command")?;
+        }
+        Ok(SimpleValuer { driver, state })
     }
 
-    /// Creates ValuerResponse for executing test `test_id`.
+    /// Creates ValuerResponse for executing test `test_id`, or queues it
+    /// if too many tests are running.
     /// Returns early if this test was already requested.
-    fn send_run_on_test_query(&mut self, test_id: TestId, live: bool) -> anyhow::Result<()> {
-        if !self.used_tests.insert(test_id) {
+    fn send_run_on_test_query(&mut self, test_id: TestId) -> anyhow::Result<()> {
+        if !self.state.used_tests.insert(test_id) {
+            return Ok(());
+        }
+        if !self.state.has_free_slot() {
+            tracing::debug!("Test {} is queued: too many tests are running", test_id);
+            self.state.queued_tests.insert(test_id);
             return Ok(());
         }
+        self.start_test(test_id)
+    }
+
+    fn start_test(&mut self, test_id: TestId) -> anyhow::Result<()> {
+        let live = self.state.test_is_live(test_id);
         let cmd = ValuerResponse::Test { test_id, live };
-        self.running_tests += 1;
+        self.state.running_tests.insert(test_id);
+        self.state.cancelled_tests.remove(&test_id);
 
         self.driver
             .send_command(&cmd)
@@ -77,14 +161,38 @@ impl<'a> SimpleValuer<'a> {
         Ok(())
     }
 
+    /// Returns test which is worth to start speculatively, if any
+    fn pick_speculative_test(&self) -> Option<TestId> {
+        self.state.max_parallel_tests?;
+        if !self.state.has_free_slot() || !self.state.queued_tests.is_empty() {
+            return None;
+        }
+        self.state
+            .fibers
+            .iter()
+            .flat_map(|fiber| fiber.speculative_tests())
+            .find(|test_id| !self.state.used_tests.contains(test_id))
+    }
+
+    /// Returns running test which can not affect any judge log, if any
+    fn pick_useless_test(&self) -> Option<TestId> {
+        self.state.running_tests.iter().copied().find(|&test_id| {
+            !self
+                .state
+                .fibers
+                .iter()
+                .any(|fiber| fiber.needs_test(test_id))
+        })
+    }
+
     /// Executes one iteration.
-    /// Returns false when valuing finishes.
-    fn step(&mut self) -> anyhow::Result<bool> {
+    fn step(&mut self) -> anyhow::Result<Step> {
         tracing::debug!("Running next step");
 
         tracing::debug!("Polling fibers");
         // do we have something new from fibers?
-        for fiber in &mut self.fibers {
+        for i in 0..self.state.fibers.len() {
+            let fiber = &mut self.state.fibers[i];
             let reply = fiber.poll();
             tracing::debug!("Polling fiber {:?}: {:?}", fiber.kind(), &reply);
             match reply {
@@ -95,29 +203,29 @@ impl<'a> SimpleValuer<'a> {
                         self.driver
                             .send_command(&live_score)
                             .context("failed to send new live score")?;
-                        return Ok(true);
+                        return Ok(Step::Progress);
                     } else {
                         tracing::debug!("Ignoring live score: kind mismatch");
                     }
                 }
                 FiberReply::Test { test_id } => {
-                    let is_live = self.fibers.iter().any(|fib| fib.test_is_live(test_id));
-                    tracing::debug!(
-                        "Step done: test execution requested (test id {}, live: {})",
-                        test_id,
-                        is_live
-                    );
-                    self.send_run_on_test_query(test_id, is_live)?;
-                    return Ok(true);
+                    tracing::debug!("Step done: test execution requested (test id {})", test_id);
+                    if let Some(notification) = self.state.finished_tests.get(&test_id) {
+                        tracing::debug!("Test {} already finished, reusing result", test_id);
+                        self.state.fibers[i].add(notification);
+                        return Ok(Step::Progress);
+                    }
+                    self.send_run_on_test_query(test_id)?;
+                    return Ok(Step::Progress);
                 }
                 FiberReply::Finish(judge_log) => {
                     tracing::info!("Step done: new judge log {:?} emitted", judge_log.kind);
                     let resp = ValuerResponse::JudgeLog(judge_log);
-                    self.running_fibers -= 1;
+                    self.state.running_fibers -= 1;
                     self.driver
                         .send_command(&resp)
                         .context("failed to submit judge log")?;
-                    return Ok(true);
+                    return Ok(Step::Progress);
                 }
                 FiberReply::None => {
                     tracing::debug!("No updates from this fiber");
@@ -125,6 +233,43 @@ impl<'a> SimpleValuer<'a> {
                 }
             }
         }
+        // queued tests which are no longer needed are just forgotten
+        let fibers = &self.state.fibers;
+        let used_tests = &mut self.state.used_tests;
+        self.state.queued_tests.retain(|test_id| {
+            let needed = fibers.iter().any(|fiber| fiber.needs_test(*test_id));
+            if !needed {
+                used_tests.remove(test_id);
+            }
+            needed
+        });
+        // can we stop some test?
+        if let Some(test_id) = self.pick_useless_test() {
+            tracing::debug!("Step done: cancelling test {}", test_id);
+            self.state.running_tests.remove(&test_id);
+            // test should be run again if requested later
+            self.state.used_tests.remove(&test_id);
+            self.state.cancelled_tests.insert(test_id);
+            self.driver
+                .send_command(&ValuerResponse::CancelTest { test_id })
+                .context("failed to send CANCEL_TEST command")?;
+            return Ok(Step::Progress);
+        }
+        // can we start some queued test?
+        if self.state.has_free_slot() {
+            if let Some(test_id) = self.state.queued_tests.iter().next().copied() {
+                tracing::debug!("Step done: starting queued test {}", test_id);
+                self.state.queued_tests.remove(&test_id);
+                self.start_test(test_id)?;
+                return Ok(Step::Progress);
+            }
+        }
+        // can we start some test in advance?
+        if let Some(test_id) = self.pick_speculative_test() {
+            tracing::debug!("Step done: speculatively requested test {}", test_id);
+            self.send_run_on_test_query(test_id)?;
+            return Ok(Step::Progress);
+        }
         // do we have pending notifications?
         if let Some(notification) = self
             .driver
@@ -132,40 +277,62 @@ impl<'a> SimpleValuer<'a> {
             .context("failed to poll for notification")?
         {
             tracing::debug!("Step done: got notification");
-            self.process_notification(notification);
-            return Ok(true);
+            self.process_notification(notification)?;
+            return Ok(Step::Progress);
         }
 
         // do we have running tests?
-        if self.running_tests != 0 {
+        if !self.state.running_tests.is_empty() {
             tracing::debug!("Step done: waiting for running tests completion");
-            return Ok(true);
+            return Ok(Step::Wait);
         }
-        if self.running_fibers != 0 {
+        if self.state.running_fibers != 0 {
             tracing::debug!("Step done: waiting for running fibers completion");
-            return Ok(true);
+            return Ok(Step::Wait);
         }
 
-        Ok(false)
+        Ok(Step::Done)
     }
 
     /// Runs to valuing completion
     pub fn exec(mut self) -> anyhow::Result<()> {
         loop {
-            let should_run = self.step()?;
-            if !should_run {
-                break;
+            match self.step()? {
+                Step::Progress => self
+                    .driver
+                    .save_snapshot(&self.state)
+                    .context("failed to save snapshot")?,
+                Step::Wait => (),
+                Step::Done => break,
             }
         }
         self.driver.send_command(&ValuerResponse::Finish)
     }
 
-    fn process_notification(&mut self, notification: TestDoneNotification) {
-        assert_ne!(self.running_tests, 0);
-        self.running_tests -= 1;
-        for fiber in self.fibers.iter_mut() {
+    fn process_notification(&mut self, notification: TestDoneNotification) -> anyhow::Result<()> {
+        if !self.state.running_tests.remove(&notification.test_id) {
+            if !self.state.cancelled_tests.remove(&notification.test_id) {
+                anyhow::bail!(
+                    "got notification about test {} which is not running",
+                    notification.test_id
+                );
+            }
+            tracing::debug!("Ignoring result of cancelled test {}", notification.test_id);
+            return Ok(());
+        }
+        // finished fiber can get notification about speculatively started test
+        for fiber in self
+            .state
+            .fibers
+            .iter_mut()
+            .filter(|fiber| !fiber.is_finished())
+        {
             fiber.add(&notification);
         }
+        self.state
+            .finished_tests
+            .insert(notification.test_id, notification);
+        Ok(())
     }
 }
 
@@ -184,9 +351,40 @@ pub mod status_util {
             kind: StatusKind::Rejected,
         }
     }
+
+    pub fn make_accepted_status() -> Status {
+        Status {
+            code: valuer_api::status_codes::ACCEPTED.to_string(),
+            kind: StatusKind::Accepted,
+        }
+    }
+
+    pub fn make_partial_status() -> Status {
+        Status {
+            code: valuer_api::status_codes::PARTIAL_SOLUTION.to_string(),
+            kind: StatusKind::Rejected,
+        }
+    }
+
+    pub fn make_rejected_status() -> Status {
+        Status {
+            code: valuer_api::status_codes::REJECTED.to_string(),
+            kind: StatusKind::Rejected,
+        }
+    }
 }
 
 #[cfg(test)]
 fn setup_log() {
     tracing_subscriber::fmt().try_init().ok();
 }
+
+#[cfg(test)]
+fn make_notification(test_id: u32, status: valuer_api::Status) -> TestDoneNotification {
+    TestDoneNotification {
+        test_id: pom::TestId::make(test_id),
+        test_status: status,
+        fraction: None,
+        resource_usage: None,
+    }
+}
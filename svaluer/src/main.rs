@@ -12,7 +12,7 @@ struct TermDriver {
 
 mod term_driver {
     use super::TermDriver;
-    use anyhow::{Context, Result};
+    use anyhow::{bail, Context, Result};
     use pom::TestId;
     use std::{
         io::{stdin, stdout, Write},
@@ -50,7 +50,10 @@ mod term_driver {
                 let group = read_value(format!("group test #{} belongs to", i))?;
                 tests.push(group);
             }
-            let info = valuer_api::ProblemInfo { tests };
+            let info = valuer_api::ProblemInfo {
+                tests,
+                test_tags: Vec::new(),
+            };
             Ok(info)
         }
 
@@ -66,6 +69,19 @@ mod term_driver {
                     } else {
                         println!("Partial solution");
                     }
+                    match judge_log.failed_test {
+                        Some(test_id) => {
+                            println!(
+                                "Status: {} on test {}",
+                                judge_log.status.code,
+                                test_id.get()
+                            )
+                        }
+                        None => println!("Status: {}", judge_log.status.code),
+                    }
+                    if let Some(time) = judge_log.max_resource_usage.and_then(|usage| usage.time) {
+                        println!("Max time: {:.2}s", time as f64 / 1000.0);
+                    }
                 }
                 valuer_api::ValuerResponse::LiveScore { score } => {
                     println!("Current score: {}", *score);
@@ -75,11 +91,20 @@ mod term_driver {
                     if *live {
                         println!("Current test: {}", test_id.get());
                     }
-                    let not_dup = self.current_tests.insert(*test_id);
-                    assert!(not_dup);
+                    if !self.current_tests.insert(*test_id) {
+                        bail!("test {} is already running", test_id.get());
+                    }
+                }
+                valuer_api::ValuerResponse::CancelTest { test_id } => {
+                    println!("Test {} is cancelled", test_id.get());
+                    self.current_tests.remove(test_id);
                 }
-                valuer_api::ValuerResponse::JudgeLog { .. } => {
+                valuer_api::ValuerResponse::Snapshot { .. } => {}
+                valuer_api::ValuerResponse::JudgeLog(judge_log) => {
                     // TODO print judge log
+                    if judge_log.kind == valuer_api::JudgeLogKind::Full {
+                        self.full_judge_log = Some(judge_log.clone());
+                    }
                 }
             }
             Ok(())
@@ -100,6 +125,8 @@ mod term_driver {
                 Ok(valuer_api::TestDoneNotification {
                     test_id: tid,
                     test_status,
+                    fraction: None,
+                    resource_usage: None,
                 })
             }
             match self.current_tests.len() {
@@ -142,12 +169,24 @@ mod json_driver {
     #[derive(Debug)]
     pub struct JsonDriver {
         chan: crossbeam_channel::Receiver<Message>,
+        /// Message which was received by `resume_state`, but not processed yet
+        pending: Option<Message>,
+        /// Send `Snapshot` after each state change.
+        /// Each snapshot contains all finished tests, so it is disabled by default.
+        snapshots: bool,
     }
-    #[derive(Deserialize)]
+    #[derive(Deserialize, Debug)]
     #[serde(untagged)]
     enum Message {
         ProblemInfo(valuer_api::ProblemInfo),
         TestDoneNotify(valuer_api::TestDoneNotification),
+        Resume(ResumeMessage),
+    }
+    /// Sent by invoker instead of ProblemInfo to resume valuing
+    #[derive(Deserialize, Debug)]
+    struct ResumeMessage {
+        /// `data` of the last `ValuerResponse::Snapshot`
+        snapshot: String,
     }
     fn json_driver_thread_func(chan: crossbeam_channel::Sender<Message>) {
         let mut buf = String::new();
@@ -175,38 +214,64 @@ mod json_driver {
     }
     const WAIT_TIMEOUT: Duration = Duration::from_millis(100);
     impl JsonDriver {
-        pub fn new() -> Self {
+        pub fn new(snapshots: bool) -> Self {
             let (send, recv) = crossbeam_channel::unbounded();
             std::thread::spawn(move || {
                 json_driver_thread_func(send);
             });
-            Self { chan: recv }
+            Self {
+                chan: recv,
+                pending: None,
+                snapshots,
+            }
         }
 
         fn poll(&mut self) -> Option<Message> {
+            if let Some(msg) = self.pending.take() {
+                return Some(msg);
+            }
             match self.chan.recv_timeout(WAIT_TIMEOUT) {
                 Ok(msg) => Some(msg),
                 Err(_err) => None,
             }
         }
-    }
 
-    impl ValuerDriver for JsonDriver {
-        fn problem_info(&mut self) -> Result<valuer_api::ProblemInfo> {
+        fn wait_message(&mut self) -> Result<Message> {
             let begin_time = Instant::now();
             const TIMEOUT: Duration = Duration::from_secs(1);
-            let message = loop {
+            loop {
                 if let Some(msg) = self.poll() {
-                    break msg;
+                    return Ok(msg);
                 }
                 if Instant::now().duration_since(begin_time) > TIMEOUT {
                     bail!("timeout");
                 }
                 std::thread::sleep(Duration::from_millis(100));
-            };
-            let problem_info = match message {
+            }
+        }
+
+        /// Returns valuer state if invoker asked to resume valuing.
+        /// Otherwise, valuing should be started from scratch.
+        pub fn resume_state(&mut self) -> Result<Option<svaluer::ValuerState>> {
+            match self.wait_message()? {
+                Message::Resume(msg) => {
+                    let state = serde_json::from_str(&msg.snapshot).context("invalid snapshot")?;
+                    Ok(Some(state))
+                }
+                msg => {
+                    self.pending = Some(msg);
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    impl ValuerDriver for JsonDriver {
+        fn problem_info(&mut self) -> Result<valuer_api::ProblemInfo> {
+            let problem_info = match self.wait_message()? {
                 Message::ProblemInfo(pi) => pi,
                 Message::TestDoneNotify(tdn) => bail!("got TestDoneNotification {:?} instead", tdn),
+                Message::Resume(msg) => bail!("got Resume {:?} instead", msg),
             };
             Ok(problem_info)
         }
@@ -224,15 +289,362 @@ mod json_driver {
                 Some(msg) => match msg {
                     Message::TestDoneNotify(tdn) => Ok(Some(tdn)),
                     Message::ProblemInfo(pi) => bail!("got ProblemInfo {:?} instead", pi),
+                    Message::Resume(msg) => bail!("got Resume {:?} instead", msg),
                 },
             }
         }
+
+        fn save_snapshot(&mut self, state: &svaluer::ValuerState) -> Result<()> {
+            if !self.snapshots {
+                return Ok(());
+            }
+            let data = serde_json::to_string(state).context("failed to serialize state")?;
+            self.send_command(&valuer_api::ValuerResponse::Snapshot { data })
+        }
     }
 }
 
-fn parse_config() -> anyhow::Result<svaluer::cfg::Config> {
-    let path = std::path::Path::new("cfg.yaml");
-    let data = std::fs::read_to_string(path).context("failed to read cfg.yaml")?;
+use replay_driver::ReplayDriver;
+
+mod replay_driver {
+    use anyhow::{bail, Context, Result};
+    use pom::TestId;
+    use serde::Deserialize;
+    use std::{
+        collections::{BTreeSet, HashMap, VecDeque},
+        path::Path,
+    };
+    use svaluer::{cfg::Config, ValuerDriver, ValuerState};
+    use valuer_api::{JudgeLog, ProblemInfo, TestDoneNotification, ValuerResponse};
+
+    /// Driver which takes test results from a file instead of invoker.
+    /// Used to re-value archived runs and to reproduce valuer bugs.
+    #[derive(Debug)]
+    pub struct ReplayDriver {
+        problem_info: Option<ProblemInfo>,
+        /// Snapshot from `Resume` message, if recorded session was resumed
+        resume_snapshot: Option<String>,
+        results: HashMap<TestId, TestDoneNotification>,
+        pending_notifications: VecDeque<TestDoneNotification>,
+        /// Requested tests without recorded result. Live run could have
+        /// cancelled them, so it is only an error if valuer waits for them.
+        missing_tests: BTreeSet<TestId>,
+        judge_logs: Vec<JudgeLog>,
+    }
+
+    /// Line of the recorded file.
+    /// JSON driver transcript also contains valuer responses, they are skipped.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Record {
+        ProblemInfo(ProblemInfo),
+        TestDoneNotify(TestDoneNotification),
+        Resume(ResumeRecord),
+        Response(ValuerResponse),
+    }
+
+    /// Sent by invoker instead of ProblemInfo to resume valuing
+    #[derive(Deserialize)]
+    struct ResumeRecord {
+        snapshot: String,
+    }
+
+    impl ReplayDriver {
+        /// Loads recorded test results. File contains one JSON message per line:
+        /// `ProblemInfo` (or `Resume`) followed by `TestDoneNotification`s, as
+        /// sent by invoker to JSON driver.
+        pub fn load(path: &Path) -> Result<Self> {
+            let data = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            Self::parse(&data)
+        }
+
+        fn parse(data: &str) -> Result<Self> {
+            let mut driver = ReplayDriver {
+                problem_info: None,
+                resume_snapshot: None,
+                results: HashMap::new(),
+                pending_notifications: VecDeque::new(),
+                missing_tests: BTreeSet::new(),
+                judge_logs: Vec::new(),
+            };
+            for (line_idx, line) in data.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record = serde_json::from_str(line)
+                    .with_context(|| format!("failed to parse line {}", line_idx + 1))?;
+                match record {
+                    Record::ProblemInfo(pi) => {
+                        if driver.problem_info.is_some() {
+                            bail!("line {}: duplicated ProblemInfo", line_idx + 1);
+                        }
+                        driver.problem_info = Some(pi);
+                    }
+                    Record::TestDoneNotify(tdn) => {
+                        // if test was run several times, latest result wins
+                        driver.results.insert(tdn.test_id, tdn);
+                    }
+                    Record::Resume(resume) => {
+                        if driver.resume_snapshot.is_some() {
+                            bail!("line {}: duplicated Resume", line_idx + 1);
+                        }
+                        driver.resume_snapshot = Some(resume.snapshot);
+                    }
+                    Record::Response(resp) => {
+                        tracing::debug!("Skipping recorded response {:?}", resp)
+                    }
+                }
+            }
+            Ok(driver)
+        }
+
+        /// Checks if recorded session was resumed from snapshot
+        pub fn is_resumed(&self) -> bool {
+            self.resume_snapshot.is_some()
+        }
+
+        /// Returns valuer state if recorded session was resumed from snapshot.
+        /// Otherwise, valuing should be started from scratch.
+        fn resume_state(&mut self) -> Result<Option<ValuerState>> {
+            match self.resume_snapshot.take() {
+                Some(snapshot) => {
+                    let state = serde_json::from_str(&snapshot).context("invalid snapshot")?;
+                    Ok(Some(state))
+                }
+                None => Ok(None),
+            }
+        }
+
+        /// Values recorded session. Resumed session keeps config from
+        /// snapshot, so `cfg` must be given if and only if session was
+        /// started from scratch.
+        pub fn exec(&mut self, cfg: Option<&Config>) -> Result<()> {
+            match (self.resume_state()?, cfg) {
+                (Some(state), None) => {
+                    tracing::info!("Resuming from recorded snapshot");
+                    svaluer::SimpleValuer::resume(self, state)?.exec()
+                }
+                (None, Some(cfg)) => svaluer::SimpleValuer::new(self, cfg)?.exec(),
+                (Some(_), Some(_)) => bail!(
+                    "config can not be used with resumed session: \
+                     valuer state is restored from recorded snapshot"
+                ),
+                (None, None) => bail!("config is required to replay session"),
+            }
+        }
+
+        /// Judge logs emitted by valuer
+        pub fn judge_logs(&self) -> &[JudgeLog] {
+            &self.judge_logs
+        }
+    }
+
+    impl ValuerDriver for ReplayDriver {
+        fn problem_info(&mut self) -> Result<ProblemInfo> {
+            match self.problem_info.clone() {
+                Some(pi) => Ok(pi),
+                None => bail!("recorded file does not contain ProblemInfo"),
+            }
+        }
+
+        fn send_command(&mut self, cmd: &ValuerResponse) -> Result<()> {
+            match cmd {
+                ValuerResponse::Test { test_id, .. } => match self.results.get(test_id) {
+                    Some(tdn) => self.pending_notifications.push_back(tdn.clone()),
+                    None => {
+                        self.missing_tests.insert(*test_id);
+                    }
+                },
+                ValuerResponse::CancelTest { test_id } => {
+                    self.pending_notifications
+                        .retain(|tdn| tdn.test_id != *test_id);
+                    self.missing_tests.remove(test_id);
+                }
+                ValuerResponse::JudgeLog(judge_log) => self.judge_logs.push(judge_log.clone()),
+                ValuerResponse::LiveScore { .. }
+                | ValuerResponse::Snapshot { .. }
+                | ValuerResponse::Finish => (),
+            }
+            Ok(())
+        }
+
+        fn poll_notification(&mut self) -> Result<Option<TestDoneNotification>> {
+            let notification = self.pending_notifications.pop_front();
+            if notification.is_none() {
+                // valuer can not progress until some running test finishes
+                if let Some(test_id) = self.missing_tests.iter().next() {
+                    bail!("no recorded result for test {}", test_id.get());
+                }
+            }
+            Ok(notification)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const SPECULATIVE_CONFIG: &str = "
+max-parallel-tests: 2
+groups:
+  - name: first
+    score: 40
+    feedback: full
+  - name: second
+    score: 60
+    feedback: full
+    deps:
+      - first
+";
+
+        fn replay(transcript: &str) -> Result<Vec<JudgeLog>> {
+            let cfg = serde_yaml::from_str(SPECULATIVE_CONFIG).unwrap();
+            let mut driver = ReplayDriver::parse(transcript)?;
+            if driver.is_resumed() {
+                driver.exec(None)?;
+            } else {
+                driver.exec(Some(&cfg))?;
+            }
+            Ok(driver.judge_logs().to_vec())
+        }
+
+        /// Collects snapshots of replayed session
+        #[derive(Debug)]
+        struct SnapshotRecorder {
+            inner: ReplayDriver,
+            snapshots: Vec<String>,
+        }
+
+        impl ValuerDriver for SnapshotRecorder {
+            fn problem_info(&mut self) -> Result<ProblemInfo> {
+                self.inner.problem_info()
+            }
+
+            fn send_command(&mut self, cmd: &ValuerResponse) -> Result<()> {
+                self.inner.send_command(cmd)
+            }
+
+            fn poll_notification(&mut self) -> Result<Option<TestDoneNotification>> {
+                self.inner.poll_notification()
+            }
+
+            fn save_snapshot(&mut self, state: &ValuerState) -> Result<()> {
+                self.snapshots.push(serde_json::to_string(state)?);
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn unrecorded_speculative_test() {
+            // test 2 was started speculatively and cancelled after test 1 failed
+            let transcript = r#"
+{"tests": ["first", "second"]}
+{"Test": {"test_id": 1, "live": true}}
+{"Test": {"test_id": 2, "live": true}}
+{"test_id": 1, "test_status": {"kind": "Rejected", "code": "WRONG_ANSWER"}}
+{"CancelTest": {"test_id": 2}}
+"#;
+            let judge_logs = replay(transcript).unwrap();
+            assert_eq!(judge_logs.len(), 2);
+            for judge_log in judge_logs {
+                assert_eq!(judge_log.score, 0);
+                assert_eq!(judge_log.status.code, "WRONG_ANSWER");
+                assert_eq!(judge_log.tests.len(), 1);
+            }
+        }
+
+        #[test]
+        fn resumed_session() {
+            let transcript = r#"
+{"tests": ["first", "second"]}
+{"test_id": 1, "test_status": {"kind": "Accepted", "code": "TEST_PASSED"}}
+{"test_id": 2, "test_status": {"kind": "Accepted", "code": "TEST_PASSED"}}
+"#;
+            let cfg = serde_yaml::from_str(SPECULATIVE_CONFIG).unwrap();
+            let mut recorder = SnapshotRecorder {
+                inner: ReplayDriver::parse(transcript).unwrap(),
+                snapshots: Vec::new(),
+            };
+            svaluer::SimpleValuer::new(&mut recorder, &cfg)
+                .unwrap()
+                .exec()
+                .unwrap();
+            // valuer was restarted before any test finished
+            let resume = serde_json::json!({ "snapshot": recorder.snapshots[0] });
+            let transcript = format!(
+                r#"
+{}
+{{"test_id": 1, "test_status": {{"kind": "Accepted", "code": "TEST_PASSED"}}}}
+{{"test_id": 2, "test_status": {{"kind": "Accepted", "code": "TEST_PASSED"}}}}
+"#,
+                resume
+            );
+            let judge_logs = replay(&transcript).unwrap();
+            assert_eq!(judge_logs.len(), 2);
+            for judge_log in judge_logs {
+                assert_eq!(judge_log.score, 100);
+                assert!(judge_log.is_full);
+            }
+        }
+
+        #[test]
+        fn resumed_session_with_config() {
+            let transcript = r#"
+{"tests": ["first", "second"]}
+"#;
+            let cfg = serde_yaml::from_str(SPECULATIVE_CONFIG).unwrap();
+            let mut recorder = SnapshotRecorder {
+                inner: ReplayDriver::parse(transcript).unwrap(),
+                snapshots: Vec::new(),
+            };
+            svaluer::SimpleValuer::new(&mut recorder, &cfg)
+                .unwrap()
+                .exec()
+                .unwrap_err();
+            let resume = serde_json::json!({ "snapshot": recorder.snapshots[0] });
+            let transcript = format!(
+                r#"
+{}
+{{"test_id": 1, "test_status": {{"kind": "Accepted", "code": "TEST_PASSED"}}}}
+"#,
+                resume
+            );
+            // config differs from the one stored in snapshot
+            let cfg: Config = serde_yaml::from_str(
+                "
+groups:
+  - name: first
+    score: 100
+    feedback: full
+",
+            )
+            .unwrap();
+            let mut driver = ReplayDriver::parse(&transcript).unwrap();
+            let err = driver.exec(Some(&cfg)).unwrap_err();
+            assert!(err.to_string().starts_with("config can not be used"));
+            assert!(driver.judge_logs().is_empty());
+        }
+
+        #[test]
+        fn missing_needed_test() {
+            // session was interrupted before test 2 finished
+            let transcript = r#"
+{"tests": ["first", "second"]}
+{"test_id": 1, "test_status": {"kind": "Accepted", "code": "TEST_PASSED"}}
+"#;
+            let err = replay(transcript).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                "no recorded result for test 2"
+            );
+        }
+    }
+}
+
+fn parse_config(path: &std::path::Path) -> anyhow::Result<svaluer::cfg::Config> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
     serde_yaml::from_str(&data).context("failed to parse config")
 }
 
@@ -241,16 +653,122 @@ fn main_cli_mode() -> anyhow::Result<()> {
         current_tests: HashSet::new(),
         full_judge_log: None,
     };
-    let cfg = parse_config()?;
+    let cfg = parse_config("cfg.yaml".as_ref())?;
     let valuer = svaluer::SimpleValuer::new(&mut driver, &cfg)?;
     valuer.exec()
 }
 
-fn main_json_mode() -> anyhow::Result<()> {
-    let mut driver = JsonDriver::new();
-    let cfg = parse_config()?;
-    let valuer = svaluer::SimpleValuer::new(&mut driver, &cfg)?;
-    valuer.exec()
+/// `snapshots` enables sending valuer state, which can be used to resume valuing
+fn main_json_mode(snapshots: bool) -> anyhow::Result<()> {
+    let mut driver = JsonDriver::new(snapshots);
+    match driver.resume_state()? {
+        Some(state) => {
+            tracing::info!("Resuming from snapshot");
+            svaluer::SimpleValuer::resume(&mut driver, state)?.exec()
+        }
+        None => {
+            let cfg = parse_config("cfg.yaml".as_ref())?;
+            svaluer::SimpleValuer::new(&mut driver, &cfg)?.exec()
+        }
+    }
+}
+
+const REPLAY_USAGE: &str =
+    "usage: svaluer replay <results or transcript> [--config <cfg.yaml>] [--output <judge logs>]";
+
+/// Values recorded test results and outputs judge logs
+fn main_replay_mode(args: &[String]) -> anyhow::Result<()> {
+    let mut results_path = None;
+    let mut config_path = None;
+    let mut output_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                config_path = Some(args.next().context(REPLAY_USAGE)?.clone());
+            }
+            "--output" => {
+                output_path = Some(args.next().context(REPLAY_USAGE)?.clone());
+            }
+            _ if results_path.is_none() && !arg.starts_with("--") => {
+                results_path = Some(arg.clone());
+            }
+            _ => anyhow::bail!("unexpected argument {}\n{}", arg, REPLAY_USAGE),
+        }
+    }
+    let results_path = results_path.context(REPLAY_USAGE)?;
+    let mut driver = ReplayDriver::load(results_path.as_ref())?;
+    let cfg = match config_path {
+        Some(path) => Some(parse_config(path.as_ref())?),
+        // resumed session is valued with config from snapshot
+        None if driver.is_resumed() => None,
+        None => Some(parse_config("cfg.yaml".as_ref())?),
+    };
+    driver.exec(cfg.as_ref())?;
+    let judge_logs = serde_json::to_string_pretty(driver.judge_logs())?;
+    match output_path {
+        Some(path) => std::fs::write(&path, judge_logs)
+            .with_context(|| format!("failed to write {}", path))?,
+        None => println!("{}", judge_logs),
+    }
+    Ok(())
+}
+
+const EXPLORE_USAGE: &str =
+    "usage: svaluer explore <problem info> [--config <cfg.yaml>] [--samples <count>]";
+
+/// Checks config on many pass/fail patterns and reports suspicious outcomes
+fn main_explore_mode(args: &[String]) -> anyhow::Result<()> {
+    let mut problem_info_path = None;
+    let mut config_path = "cfg.yaml".to_string();
+    let mut samples = 10000;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                config_path = args.next().context(EXPLORE_USAGE)?.clone();
+            }
+            "--samples" => {
+                samples = args
+                    .next()
+                    .context(EXPLORE_USAGE)?
+                    .parse()
+                    .context("invalid samples count")?;
+            }
+            _ if problem_info_path.is_none() && !arg.starts_with("--") => {
+                problem_info_path = Some(arg.clone());
+            }
+            _ => anyhow::bail!("unexpected argument {}\n{}", arg, EXPLORE_USAGE),
+        }
+    }
+    let problem_info_path = problem_info_path.context(EXPLORE_USAGE)?;
+    let cfg = parse_config(config_path.as_ref())?;
+    let problem_info = std::fs::read_to_string(&problem_info_path)
+        .with_context(|| format!("failed to read {}", problem_info_path))?;
+    let problem_info =
+        serde_json::from_str(&problem_info).context("failed to parse problem info")?;
+    let report = svaluer::explore::explore(&cfg, &problem_info, samples)?;
+
+    if report.exhaustive {
+        println!("Checked all {} patterns", report.patterns);
+    } else {
+        println!("Checked {} sampled patterns", report.patterns);
+    }
+    let scores: Vec<_> = report
+        .reachable_scores
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    println!("Reachable scores: {}", scores.join(", "));
+    println!("Full solution reachable: {}", report.full_reachable);
+    let warnings = report.warnings();
+    for warning in &warnings {
+        println!("Warning: {}", warning);
+    }
+    if !warnings.is_empty() {
+        anyhow::bail!("config has {} problem(s)", warnings.len());
+    }
+    Ok(())
 }
 
 fn main() -> anyhow::Result<()> {
@@ -258,10 +776,17 @@ fn main() -> anyhow::Result<()> {
         .with_env_filter(tracing_subscriber::EnvFilter::from_env("JJS_SVALUER_LOG"))
         .init();
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
     let json_mode = std::env::var("JJS_VALUER").is_ok();
-    if json_mode {
+    if args.first().map(String::as_str) == Some("replay") {
+        tracing::info!("Mode: replay");
+        main_replay_mode(&args[1..])?
+    } else if args.first().map(String::as_str) == Some("explore") {
+        tracing::info!("Mode: explore");
+        main_explore_mode(&args[1..])?
+    } else if json_mode {
         tracing::info!("Mode: JSON");
-        main_json_mode()?
+        main_json_mode(args.iter().any(|arg| arg == "--snapshots"))?
     } else {
         tracing::info!("Mode: CLI");
         main_cli_mode()?
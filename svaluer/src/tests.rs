@@ -1,9 +1,12 @@
 use super::*;
-use status_util::{make_err_status, make_ok_status};
+use status_util::{
+    make_accepted_status, make_err_status, make_ok_status, make_partial_status,
+    make_rejected_status,
+};
 use std::collections::VecDeque;
 use valuer_api::{
-    JudgeLog, JudgeLogSubtaskRow, JudgeLogTestRow, Status, SubtaskId, SubtaskVisibleComponents,
-    TestVisibleComponents,
+    JudgeLog, JudgeLogSubtaskRow, JudgeLogTestRow, Status, StatusKind, SubtaskId, SubtaskState,
+    SubtaskVisibleComponents, TestVisibleComponents,
 };
 
 #[derive(Debug)]
@@ -18,8 +21,13 @@ struct MockDriver {
     tests: VecDeque<TestMock>,
     pending_notifications: VecDeque<TestDoneNotification>,
     live_scores: VecDeque<u32>,
+    cancelled_tests: VecDeque<TestId>,
     problem_info: ProblemInfo,
     judge_logs: Vec<JudgeLog>,
+    /// Serialized snapshots, received from valuer
+    snapshots: Vec<String>,
+    /// If set, valuer must not run more tests at once
+    max_running_tests: Option<usize>,
 }
 impl MockDriver {
     fn new(problem_info: ProblemInfo) -> Self {
@@ -27,20 +35,33 @@ impl MockDriver {
             tests: VecDeque::new(),
             problem_info,
             live_scores: VecDeque::new(),
+            cancelled_tests: VecDeque::new(),
             pending_notifications: VecDeque::new(),
             judge_logs: Vec::new(),
+            snapshots: Vec::new(),
+            max_running_tests: None,
         }
     }
 
+    fn set_max_running_tests(&mut self, count: usize) -> &mut Self {
+        self.max_running_tests = Some(count);
+        self
+    }
+
     fn add_test(&mut self, test_id: u32, live: bool, ok: bool) -> &mut Self {
+        let status = if ok {
+            make_ok_status()
+        } else {
+            make_err_status()
+        };
+        self.add_test_with_status(test_id, live, status)
+    }
+
+    fn add_test_with_status(&mut self, test_id: u32, live: bool, status: Status) -> &mut Self {
         let mock = TestMock {
             test_id: TestId::make(test_id),
             live,
-            status: if ok {
-                make_ok_status()
-            } else {
-                make_err_status()
-            },
+            status,
         };
         self.tests.push_back(mock);
         self
@@ -51,6 +72,11 @@ impl MockDriver {
         self
     }
 
+    fn add_cancel(&mut self, test_id: u32) -> &mut Self {
+        self.cancelled_tests.push_back(TestId::make(test_id));
+        self
+    }
+
     fn add_live_score(&mut self, score: u32) -> &mut Self {
         self.live_scores.push_back(score);
         self
@@ -66,6 +92,9 @@ impl MockDriver {
         if !self.tests.is_empty() {
             panic!("not all tests were executed");
         }
+        if !self.cancelled_tests.is_empty() {
+            panic!("not all tests were cancelled");
+        }
         if let Some(judge_log) = self.judge_logs.first() {
             panic!("judge log {:?} was not emitted", judge_log.kind);
         }
@@ -85,6 +114,21 @@ impl MockDriver {
         }
     }
 
+    fn check_cancel(&mut self, test_id: TestId) {
+        match self.cancelled_tests.pop_front() {
+            Some(expected) => {
+                if expected != test_id {
+                    panic!(
+                        "expected cancel of {}, but valuer cancelled {}",
+                        expected.get(),
+                        test_id.get()
+                    );
+                }
+            }
+            None => panic!("no cancels expected, but got {}", test_id.get()),
+        }
+    }
+
     fn check_test(&mut self, test_id: TestId, live: bool) {
         match self.tests.pop_front() {
             Some(mock) => {
@@ -98,9 +142,20 @@ impl MockDriver {
                 if mock.live != live {
                     panic!("live flag differs: expected {}, actual {}", mock.live, live);
                 }
+                if let Some(max) = self.max_running_tests {
+                    if self.pending_notifications.len() >= max {
+                        panic!(
+                            "test {} started while {} tests are running",
+                            test_id.get(),
+                            max
+                        );
+                    }
+                }
                 self.pending_notifications.push_back(TestDoneNotification {
                     test_id: mock.test_id,
                     test_status: mock.status,
+                    fraction: None,
+                    resource_usage: None,
                 })
             }
             None => panic!(
@@ -138,6 +193,8 @@ impl MockDriver {
         assert_eq!(expected.score, judge_log.score);
         assert_eq!(expected.kind, judge_log.kind);
         assert_eq!(expected.is_full, judge_log.is_full);
+        assert_eq!(expected.status, judge_log.status);
+        assert_eq!(expected.failed_test, judge_log.failed_test);
         // In case new field is added, of course an assert should be added.
         // But as additional check, compare full logs.
         assert_eq!(&expected, judge_log);
@@ -150,6 +207,13 @@ impl MockDriver {
         let valuer = SimpleValuer::new(self, &cfg).unwrap();
         valuer.exec().unwrap();
     }
+
+    fn resume(&mut self, snapshot: &str) {
+        crate::setup_log();
+        let state = serde_json::from_str(snapshot).expect("failed to parse snapshot");
+        let valuer = SimpleValuer::resume(self, state).unwrap();
+        valuer.exec().unwrap();
+    }
 }
 
 impl ValuerDriver for MockDriver {
@@ -163,6 +227,8 @@ impl ValuerDriver for MockDriver {
             ValuerResponse::JudgeLog(judge_log) => self.check_judge_log(judge_log),
             ValuerResponse::LiveScore { score } => self.check_live_score(*score),
             ValuerResponse::Test { test_id, live } => self.check_test(*test_id, *live),
+            ValuerResponse::CancelTest { test_id } => self.check_cancel(*test_id),
+            ValuerResponse::Snapshot { .. } => panic!("snapshot must be sent via save_snapshot"),
         }
         Ok(())
     }
@@ -170,12 +236,18 @@ impl ValuerDriver for MockDriver {
     fn poll_notification(&mut self) -> Result<Option<TestDoneNotification>> {
         Ok(self.pending_notifications.pop_front())
     }
+
+    fn save_snapshot(&mut self, state: &ValuerState) -> Result<()> {
+        self.snapshots.push(serde_json::to_string(state)?);
+        Ok(())
+    }
 }
 
 mod simple {
     use super::*;
-    #[test]
-    fn simple_ok() {
+    /// Judge logs for solution passing `online` and `offline` groups,
+    /// where only `online` is shown to contestant
+    fn online_offline_logs() -> (JudgeLog, JudgeLog) {
         let full_log = JudgeLog {
             is_full: true,
             kind: JudgeLogKind::Full,
@@ -184,26 +256,41 @@ mod simple {
                     test_id: TestId::make(1),
                     status: make_ok_status(),
                     components: TestVisibleComponents::all(),
+                    resource_usage: None,
                 },
                 JudgeLogTestRow {
                     test_id: TestId::make(2),
                     status: make_ok_status(),
                     components: TestVisibleComponents::all(),
+                    resource_usage: None,
                 },
             ],
             subtasks: vec![
                 JudgeLogSubtaskRow {
                     subtask_id: SubtaskId::make(1),
+                    name: "online".to_string(),
+                    state: SubtaskState::Passed,
+                    skipped_because: None,
+                    failed_test: None,
                     score: 64,
                     components: SubtaskVisibleComponents::SCORE,
+                    max_resource_usage: None,
                 },
                 JudgeLogSubtaskRow {
                     subtask_id: SubtaskId::make(2),
+                    name: "offline".to_string(),
+                    state: SubtaskState::Passed,
+                    skipped_because: None,
+                    failed_test: None,
                     score: 36,
                     components: SubtaskVisibleComponents::SCORE,
+                    max_resource_usage: None,
                 },
             ],
             score: 100,
+            status: make_accepted_status(),
+            failed_test: None,
+            max_resource_usage: None,
         };
         let mut contestant_log = full_log.clone();
         contestant_log.kind = JudgeLogKind::Contestant;
@@ -212,8 +299,18 @@ mod simple {
         contestant_log.tests[0].components =
             TestVisibleComponents::STATUS | TestVisibleComponents::RESOURCE_USAGE;
         contestant_log.score = 64;
+        // contestant does not know whether `offline` is passed
+        contestant_log.is_full = false;
+        contestant_log.status = make_partial_status();
+        (full_log, contestant_log)
+    }
+
+    #[test]
+    fn simple_ok() {
+        let (full_log, contestant_log) = online_offline_logs();
         MockDriver::new(ProblemInfo {
             tests: vec!["online".to_string(), "offline".to_string()],
+            test_tags: Vec::new(),
         })
         .add_test(1, true, true)
         .add_test(2, false, true)
@@ -235,6 +332,163 @@ groups:
         );
     }
 
+    #[test]
+    fn open_tests_count() {
+        let (full_log, contestant_log) = online_offline_logs();
+        MockDriver::new(ProblemInfo {
+            tests: vec!["online".to_string(), "offline".to_string()],
+            test_tags: Vec::new(),
+        })
+        .add_test(1, true, true)
+        .add_test(2, false, true)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .add_live_score(64)
+        .exec(
+            "
+open-tests-count: 1
+groups:
+  - name: online
+    feedback: brief
+    score: 64
+  - name: offline
+    feedback: brief
+    score: 36
+    deps:
+      - online
+            ",
+        );
+    }
+
+    #[test]
+    fn open_tests_count_inside_group() {
+        let row = |test_id, status: Status| JudgeLogTestRow {
+            test_id: TestId::make(test_id),
+            status,
+            components: TestVisibleComponents::all(),
+            resource_usage: None,
+        };
+        let subtask = |id, name: &str, state, failed_test: Option<u32>, score| JudgeLogSubtaskRow {
+            subtask_id: SubtaskId::make(id),
+            name: name.to_string(),
+            state,
+            skipped_because: None,
+            failed_test: failed_test.map(TestId::make),
+            score,
+            components: SubtaskVisibleComponents::all(),
+            max_resource_usage: None,
+        };
+        let full_log = JudgeLog {
+            is_full: false,
+            kind: JudgeLogKind::Full,
+            tests: vec![
+                row(1, make_ok_status()),
+                row(3, make_err_status()),
+                row(2, make_ok_status()),
+            ],
+            subtasks: vec![
+                subtask(1, "first", SubtaskState::Passed, None, 40),
+                subtask(2, "second", SubtaskState::Failed, Some(3), 0),
+            ],
+            score: 40,
+            status: make_partial_status(),
+            failed_test: Some(TestId::make(3)),
+            max_resource_usage: None,
+        };
+        // `second` is valued over test 2 only, and test 3 is not disclosed
+        let contestant_log = JudgeLog {
+            is_full: false,
+            kind: JudgeLogKind::Contestant,
+            tests: vec![row(1, make_ok_status()), row(2, make_ok_status())],
+            subtasks: vec![
+                subtask(1, "first", SubtaskState::Passed, None, 40),
+                subtask(2, "second", SubtaskState::Passed, None, 60),
+            ],
+            score: 100,
+            status: make_partial_status(),
+            failed_test: None,
+            max_resource_usage: None,
+        };
+        MockDriver::new(ProblemInfo {
+            tests: vec![
+                "first".to_string(),
+                "second".to_string(),
+                "second".to_string(),
+            ],
+            test_tags: Vec::new(),
+        })
+        .add_test(1, true, true)
+        .add_test(2, true, true)
+        .add_test(3, false, false)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .add_live_score(40)
+        .add_live_score(100)
+        .exec(
+            "
+open-tests-count: 2
+groups:
+  - name: first
+    feedback: full
+    score: 40
+  - name: second
+    feedback: full
+    score: 60
+            ",
+        );
+    }
+
+    const ONLINE_OFFLINE_CONFIG: &str = "
+groups:
+  - name: online
+    feedback: brief
+    score: 64
+  - name: offline
+    feedback: hidden
+    score: 36
+    deps:
+      - online
+";
+
+    #[test]
+    fn resume_from_snapshot() {
+        let problem_info = ProblemInfo {
+            tests: vec!["online".to_string(), "offline".to_string()],
+            test_tags: Vec::new(),
+        };
+        let (full_log, contestant_log) = online_offline_logs();
+        let mut driver = MockDriver::new(problem_info.clone());
+        driver
+            .add_test(1, true, true)
+            .add_test(2, false, true)
+            .add_judge_log(full_log.clone())
+            .add_judge_log(contestant_log.clone())
+            .add_live_score(64)
+            .exec(ONLINE_OFFLINE_CONFIG);
+        // valuer was restarted before first test finished
+        MockDriver::new(problem_info.clone())
+            .add_test(1, true, true)
+            .add_test(2, false, true)
+            .add_judge_log(full_log.clone())
+            .add_judge_log(contestant_log)
+            .add_live_score(64)
+            .resume(&driver.snapshots[0]);
+        // valuer was restarted while last test was running
+        let last_running = driver
+            .snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| {
+                let state: ValuerState = serde_json::from_str(snapshot).unwrap();
+                !state.running_tests.is_empty()
+            })
+            .expect("snapshot with running test not found");
+        MockDriver::new(problem_info)
+            .add_test(2, false, true)
+            .add_judge_log(full_log)
+            .resume(last_running);
+    }
+
     #[test]
     fn status_err() {
         let full_log = JudgeLog {
@@ -244,25 +498,40 @@ groups:
                 test_id: TestId::make(1),
                 status: make_err_status(),
                 components: TestVisibleComponents::all(),
+                resource_usage: None,
             }],
             subtasks: vec![
                 JudgeLogSubtaskRow {
                     subtask_id: SubtaskId::make(1),
+                    name: "samples".to_string(),
+                    state: SubtaskState::Failed,
+                    skipped_because: None,
+                    failed_test: Some(TestId::make(1)),
                     score: 0,
                     components: SubtaskVisibleComponents::all(),
+                    max_resource_usage: None,
                 },
                 JudgeLogSubtaskRow {
                     subtask_id: SubtaskId::make(2),
+                    name: "online".to_string(),
+                    state: SubtaskState::Skipped,
+                    skipped_because: Some(SubtaskId::make(1)),
+                    failed_test: None,
                     score: 0,
                     components: SubtaskVisibleComponents::all(),
+                    max_resource_usage: None,
                 },
             ],
             score: 0,
+            status: make_err_status(),
+            failed_test: Some(TestId::make(1)),
+            max_resource_usage: None,
         };
         let mut contestant_log = full_log.clone();
         contestant_log.kind = JudgeLogKind::Contestant;
         MockDriver::new(ProblemInfo {
             tests: vec!["samples".to_string(), "online".to_string()],
+            test_tags: Vec::new(),
         })
         .add_test(1, true, false)
         .add_judge_log(full_log)
@@ -281,4 +550,618 @@ groups:
                 ",
         );
     }
+
+    #[test]
+    fn first_failed_test_status() {
+        let status = |kind, code: &str| Status {
+            kind,
+            code: code.to_string(),
+        };
+        let wa = status(StatusKind::Rejected, valuer_api::status_codes::WRONG_ANSWER);
+        let tle = status(
+            StatusKind::Rejected,
+            valuer_api::status_codes::TIME_LIMIT_EXCEEDED,
+        );
+        let row = |test_id, status: &Status| JudgeLogTestRow {
+            test_id: TestId::make(test_id),
+            status: status.clone(),
+            components: TestVisibleComponents::all(),
+            resource_usage: None,
+        };
+        let full_log = JudgeLog {
+            is_full: false,
+            kind: JudgeLogKind::Full,
+            tests: vec![row(2, &wa), row(3, &tle), row(1, &make_ok_status())],
+            subtasks: vec![JudgeLogSubtaskRow {
+                subtask_id: SubtaskId::make(1),
+                name: "main".to_string(),
+                state: SubtaskState::Failed,
+                skipped_because: None,
+                failed_test: Some(TestId::make(2)),
+                score: 0,
+                components: SubtaskVisibleComponents::all(),
+                max_resource_usage: None,
+            }],
+            score: 0,
+            status: wa.clone(),
+            failed_test: Some(TestId::make(2)),
+            max_resource_usage: None,
+        };
+        let mut contestant_log = full_log.clone();
+        contestant_log.kind = JudgeLogKind::Contestant;
+        MockDriver::new(ProblemInfo {
+            tests: vec!["main".to_string(); 3],
+            test_tags: Vec::new(),
+        })
+        .add_test(1, true, true)
+        .add_test_with_status(2, true, wa.clone())
+        .add_test_with_status(3, true, tle.clone())
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .exec(
+            "
+groups:
+  - name: main
+    score: 100
+    feedback: full
+    run_to_first_failure: false
+            ",
+        );
+    }
+
+    #[test]
+    fn hidden_failed_test_status() {
+        let wa = Status {
+            kind: StatusKind::Rejected,
+            code: valuer_api::status_codes::WRONG_ANSWER.to_string(),
+        };
+        let subtask = JudgeLogSubtaskRow {
+            subtask_id: SubtaskId::make(1),
+            name: "main".to_string(),
+            state: SubtaskState::Failed,
+            skipped_because: None,
+            failed_test: Some(TestId::make(1)),
+            score: 0,
+            components: SubtaskVisibleComponents::all(),
+            max_resource_usage: None,
+        };
+        let full_log = JudgeLog {
+            is_full: false,
+            kind: JudgeLogKind::Full,
+            tests: vec![JudgeLogTestRow {
+                test_id: TestId::make(1),
+                status: wa.clone(),
+                components: TestVisibleComponents::all(),
+                resource_usage: None,
+            }],
+            subtasks: vec![subtask.clone()],
+            score: 0,
+            status: wa.clone(),
+            failed_test: Some(TestId::make(1)),
+            max_resource_usage: None,
+        };
+        // contestant sees neither the test nor its verdict
+        let contestant_log = JudgeLog {
+            kind: JudgeLogKind::Contestant,
+            tests: Vec::new(),
+            subtasks: vec![JudgeLogSubtaskRow {
+                failed_test: None,
+                components: SubtaskVisibleComponents::SCORE,
+                ..subtask.clone()
+            }],
+            status: make_rejected_status(),
+            failed_test: None,
+            ..full_log.clone()
+        };
+        MockDriver::new(ProblemInfo {
+            tests: vec!["main".to_string(); 2],
+            test_tags: Vec::new(),
+        })
+        .add_test_with_status(1, true, wa)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .exec(
+            "
+groups:
+  - name: main
+    score: 100
+    feedback:
+      custom:
+        shown_tests: none
+            ",
+        );
+    }
+
+    #[test]
+    fn failed_test_without_status() {
+        let subtask = JudgeLogSubtaskRow {
+            subtask_id: SubtaskId::make(1),
+            name: "main".to_string(),
+            state: SubtaskState::Failed,
+            skipped_because: None,
+            failed_test: Some(TestId::make(2)),
+            score: 0,
+            components: SubtaskVisibleComponents::all(),
+            max_resource_usage: None,
+        };
+        let row = |test_id, status: Status, components| JudgeLogTestRow {
+            test_id: TestId::make(test_id),
+            status,
+            components,
+            resource_usage: None,
+        };
+        let full_log = JudgeLog {
+            is_full: false,
+            kind: JudgeLogKind::Full,
+            tests: vec![
+                row(2, make_err_status(), TestVisibleComponents::all()),
+                row(1, make_ok_status(), TestVisibleComponents::all()),
+            ],
+            subtasks: vec![subtask.clone()],
+            score: 0,
+            status: make_err_status(),
+            failed_test: Some(TestId::make(2)),
+            max_resource_usage: None,
+        };
+        // tests are shown, but contestant can not tell which one failed
+        let contestant_log = JudgeLog {
+            kind: JudgeLogKind::Contestant,
+            tests: vec![
+                row(2, make_err_status(), TestVisibleComponents::TEST_DATA),
+                row(1, make_ok_status(), TestVisibleComponents::TEST_DATA),
+            ],
+            subtasks: vec![JudgeLogSubtaskRow {
+                failed_test: None,
+                components: SubtaskVisibleComponents::SCORE,
+                ..subtask
+            }],
+            status: make_rejected_status(),
+            failed_test: None,
+            ..full_log.clone()
+        };
+        MockDriver::new(ProblemInfo {
+            tests: vec!["main".to_string(); 2],
+            test_tags: Vec::new(),
+        })
+        .add_test(1, true, true)
+        .add_test(2, true, false)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .exec(
+            "
+groups:
+  - name: main
+    score: 100
+    feedback:
+      custom:
+        tests: [test-data]
+            ",
+        );
+    }
+
+    #[test]
+    fn all_groups_hidden() {
+        let full_log = JudgeLog {
+            is_full: true,
+            kind: JudgeLogKind::Full,
+            tests: vec![JudgeLogTestRow {
+                test_id: TestId::make(1),
+                status: make_ok_status(),
+                components: TestVisibleComponents::all(),
+                resource_usage: None,
+            }],
+            subtasks: vec![JudgeLogSubtaskRow {
+                subtask_id: SubtaskId::make(1),
+                name: "main".to_string(),
+                state: SubtaskState::Passed,
+                skipped_because: None,
+                failed_test: None,
+                score: 100,
+                components: SubtaskVisibleComponents::all(),
+                max_resource_usage: None,
+            }],
+            score: 100,
+            status: make_accepted_status(),
+            failed_test: None,
+            max_resource_usage: None,
+        };
+        // contestant log is empty, so it must not claim full solution
+        let contestant_log = JudgeLog {
+            kind: JudgeLogKind::Contestant,
+            ..Default::default()
+        };
+        MockDriver::new(ProblemInfo {
+            tests: vec!["main".to_string()],
+            test_tags: Vec::new(),
+        })
+        .add_test(1, false, true)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .exec(
+            "
+groups:
+  - name: main
+    score: 100
+    feedback: hidden
+            ",
+        );
+    }
+
+    #[test]
+    fn test_score_runs_all_tests() {
+        let row = |test_id, status: Status| JudgeLogTestRow {
+            test_id: TestId::make(test_id),
+            status,
+            components: TestVisibleComponents::all(),
+            resource_usage: None,
+        };
+        let full_log = JudgeLog {
+            is_full: false,
+            kind: JudgeLogKind::Full,
+            tests: vec![
+                row(2, make_err_status()),
+                row(1, make_ok_status()),
+                row(3, make_ok_status()),
+            ],
+            subtasks: vec![JudgeLogSubtaskRow {
+                subtask_id: SubtaskId::make(1),
+                name: "main".to_string(),
+                state: SubtaskState::Failed,
+                skipped_because: None,
+                failed_test: Some(TestId::make(2)),
+                score: 20,
+                components: SubtaskVisibleComponents::all(),
+                max_resource_usage: None,
+            }],
+            score: 20,
+            status: status_util::make_partial_status(),
+            failed_test: Some(TestId::make(2)),
+            max_resource_usage: None,
+        };
+        let mut contestant_log = full_log.clone();
+        contestant_log.kind = JudgeLogKind::Contestant;
+        MockDriver::new(ProblemInfo {
+            tests: vec!["main".to_string(); 3],
+            test_tags: Vec::new(),
+        })
+        .add_test(1, true, true)
+        .add_test(2, true, false)
+        // failed test does not stop the group, because each test is scored
+        .add_test(3, true, true)
+        .add_live_score(20)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .exec(
+            "
+groups:
+  - name: main
+    score: 0
+    test_score: 10
+    feedback: full
+            ",
+        );
+    }
+
+    #[test]
+    fn tests_in_several_groups() {
+        let row = |test_id, components| JudgeLogTestRow {
+            test_id: TestId::make(test_id),
+            status: make_ok_status(),
+            components,
+            resource_usage: None,
+        };
+        let subtask = |id, name: &str, score, components| JudgeLogSubtaskRow {
+            subtask_id: SubtaskId::make(id),
+            name: name.to_string(),
+            state: SubtaskState::Passed,
+            skipped_because: None,
+            failed_test: None,
+            score,
+            components,
+            max_resource_usage: None,
+        };
+        let all = TestVisibleComponents::all();
+        let full_log = JudgeLog {
+            is_full: true,
+            kind: JudgeLogKind::Full,
+            // each test is reported once, even if it belongs to several groups
+            tests: vec![row(1, all), row(2, all), row(3, all)],
+            subtasks: vec![
+                subtask(1, "samples", 0, SubtaskVisibleComponents::all()),
+                subtask(2, "first", 40, SubtaskVisibleComponents::all()),
+                subtask(3, "full", 60, SubtaskVisibleComponents::all()),
+            ],
+            score: 100,
+            status: make_accepted_status(),
+            failed_test: None,
+            max_resource_usage: None,
+        };
+        let mut contestant_log = full_log.clone();
+        contestant_log.kind = JudgeLogKind::Contestant;
+        for row in &mut contestant_log.tests[1..] {
+            row.components = TestVisibleComponents::STATUS | TestVisibleComponents::RESOURCE_USAGE;
+        }
+        for subtask in &mut contestant_log.subtasks[1..] {
+            subtask.components = SubtaskVisibleComponents::SCORE;
+        }
+        MockDriver::new(ProblemInfo {
+            tests: vec![
+                "samples".to_string(),
+                "first".to_string(),
+                "full".to_string(),
+            ],
+            test_tags: vec![
+                vec!["first".to_string(), "full".to_string()],
+                vec!["full".to_string()],
+            ],
+        })
+        // each test is run only once
+        .add_test(1, true, true)
+        .add_test(2, true, true)
+        .add_test(3, true, true)
+        .add_live_score(40)
+        .add_live_score(100)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .exec(
+            "
+groups:
+  - name: samples
+    score: 0
+    feedback: full
+  - name: first
+    score: 40
+    feedback: brief
+    deps:
+      - samples
+  - name: full
+    score: 60
+    feedback: brief
+    deps:
+      - first
+            ",
+        );
+    }
+
+    const SPECULATIVE_CONFIG: &str = "
+max-parallel-tests: 2
+groups:
+  - name: first
+    score: 40
+    feedback: full
+  - name: second
+    score: 60
+    feedback: full
+    deps:
+      - first
+    ";
+
+    fn speculative_logs(
+        tests: Vec<JudgeLogTestRow>,
+        subtasks: Vec<JudgeLogSubtaskRow>,
+    ) -> (JudgeLog, JudgeLog) {
+        let score = subtasks.iter().map(|row| row.score).sum();
+        let failed_test = subtasks.iter().find_map(|row| row.failed_test);
+        let full_log = JudgeLog {
+            is_full: failed_test.is_none(),
+            kind: JudgeLogKind::Full,
+            tests,
+            subtasks,
+            score,
+            status: if failed_test.is_none() {
+                make_accepted_status()
+            } else {
+                make_err_status()
+            },
+            failed_test,
+            max_resource_usage: None,
+        };
+        let mut contestant_log = full_log.clone();
+        contestant_log.kind = JudgeLogKind::Contestant;
+        (full_log, contestant_log)
+    }
+
+    fn speculative_subtask(
+        id: u32,
+        name: &str,
+        score: u32,
+        state: SubtaskState,
+    ) -> JudgeLogSubtaskRow {
+        JudgeLogSubtaskRow {
+            subtask_id: SubtaskId::make(id),
+            name: name.to_string(),
+            state,
+            skipped_because: None,
+            failed_test: None,
+            score,
+            components: SubtaskVisibleComponents::all(),
+            max_resource_usage: None,
+        }
+    }
+
+    #[test]
+    fn speculative_results_are_reused() {
+        let row = |test_id| JudgeLogTestRow {
+            test_id: TestId::make(test_id),
+            status: make_ok_status(),
+            components: TestVisibleComponents::all(),
+            resource_usage: None,
+        };
+        let (full_log, contestant_log) = speculative_logs(
+            vec![row(1), row(2), row(3)],
+            vec![
+                speculative_subtask(1, "first", 40, SubtaskState::Passed),
+                speculative_subtask(2, "second", 60, SubtaskState::Passed),
+            ],
+        );
+        MockDriver::new(ProblemInfo {
+            tests: vec![
+                "first".to_string(),
+                "first".to_string(),
+                "second".to_string(),
+            ],
+            test_tags: Vec::new(),
+        })
+        // tests of running group are started in parallel
+        .add_test(1, true, true)
+        .add_test(2, true, true)
+        // started before `first` group passed
+        .add_test(3, true, true)
+        .add_live_score(40)
+        .add_live_score(100)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .exec(SPECULATIVE_CONFIG);
+    }
+
+    #[test]
+    fn speculative_results_are_discarded() {
+        let (full_log, contestant_log) = speculative_logs(
+            vec![JudgeLogTestRow {
+                test_id: TestId::make(1),
+                status: make_err_status(),
+                components: TestVisibleComponents::all(),
+                resource_usage: None,
+            }],
+            vec![
+                JudgeLogSubtaskRow {
+                    failed_test: Some(TestId::make(1)),
+                    ..speculative_subtask(1, "first", 0, SubtaskState::Failed)
+                },
+                JudgeLogSubtaskRow {
+                    skipped_because: Some(SubtaskId::make(1)),
+                    ..speculative_subtask(2, "second", 0, SubtaskState::Skipped)
+                },
+            ],
+        );
+        MockDriver::new(ProblemInfo {
+            tests: vec![
+                "first".to_string(),
+                "first".to_string(),
+                "second".to_string(),
+            ],
+            test_tags: Vec::new(),
+        })
+        .add_test(1, true, false)
+        .add_test(2, true, true)
+        // `first` failed, so test 2 can not change anything
+        .add_cancel(2)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .exec(SPECULATIVE_CONFIG);
+    }
+
+    #[test]
+    fn cancelled_test_finishes_after_resume() {
+        let (full_log, contestant_log) = speculative_logs(
+            vec![JudgeLogTestRow {
+                test_id: TestId::make(1),
+                status: make_err_status(),
+                components: TestVisibleComponents::all(),
+                resource_usage: None,
+            }],
+            vec![
+                JudgeLogSubtaskRow {
+                    failed_test: Some(TestId::make(1)),
+                    ..speculative_subtask(1, "first", 0, SubtaskState::Failed)
+                },
+                JudgeLogSubtaskRow {
+                    skipped_because: Some(SubtaskId::make(1)),
+                    ..speculative_subtask(2, "second", 0, SubtaskState::Skipped)
+                },
+            ],
+        );
+        let problem_info = ProblemInfo {
+            tests: vec![
+                "first".to_string(),
+                "first".to_string(),
+                "second".to_string(),
+            ],
+            test_tags: Vec::new(),
+        };
+        let mut driver = MockDriver::new(problem_info.clone());
+        driver
+            .add_test(1, true, false)
+            .add_test(2, true, true)
+            .add_cancel(2)
+            .add_judge_log(full_log)
+            .add_judge_log(contestant_log)
+            .exec(SPECULATIVE_CONFIG);
+        let after_cancel = driver
+            .snapshots
+            .iter()
+            .find(|snapshot| {
+                let state: ValuerState = serde_json::from_str(snapshot).unwrap();
+                !state.cancelled_tests.is_empty()
+            })
+            .expect("snapshot with cancelled test not found");
+        // valuer was restarted, and then cancelled test finished anyway
+        let mut resumed = MockDriver::new(problem_info);
+        resumed
+            .pending_notifications
+            .push_back(crate::make_notification(2, make_ok_status()));
+        resumed.resume(after_cancel);
+    }
+
+    #[test]
+    fn unexpected_notification_is_error() {
+        let mut driver = MockDriver::new(ProblemInfo {
+            tests: vec!["main".to_string()],
+            test_tags: Vec::new(),
+        });
+        driver.add_test(1, true, true);
+        driver
+            .pending_notifications
+            .push_back(crate::make_notification(2, make_ok_status()));
+        let cfg = serde_yaml::from_str(
+            "
+groups:
+  - name: main
+    score: 100
+    feedback: full
+            ",
+        )
+        .unwrap();
+        let err = SimpleValuer::new(&mut driver, &cfg)
+            .unwrap()
+            .exec()
+            .unwrap_err();
+        assert!(err.to_string().contains("not running"), "{:#}", err);
+    }
+
+    #[test]
+    fn parallel_tests_are_limited() {
+        let row = |test_id| JudgeLogTestRow {
+            test_id: TestId::make(test_id),
+            status: make_ok_status(),
+            components: TestVisibleComponents::all(),
+            resource_usage: None,
+        };
+        let (full_log, contestant_log) = speculative_logs(
+            vec![row(1), row(2), row(3), row(4)],
+            vec![speculative_subtask(1, "main", 100, SubtaskState::Passed)],
+        );
+        MockDriver::new(ProblemInfo {
+            tests: vec!["main".to_string(); 4],
+            test_tags: Vec::new(),
+        })
+        .set_max_running_tests(2)
+        .add_test(1, true, true)
+        .add_test(2, true, true)
+        // requested by group at once, but started only when slots are freed
+        .add_test(3, true, true)
+        .add_test(4, true, true)
+        .add_live_score(100)
+        .add_judge_log(full_log)
+        .add_judge_log(contestant_log)
+        .exec(
+            "
+max-parallel-tests: 2
+groups:
+  - name: main
+    score: 100
+    feedback: full
+            ",
+        );
+    }
 }
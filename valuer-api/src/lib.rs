@@ -69,7 +69,7 @@ pub mod status_codes {
     );
 
     // aggregated status codes
-    declare_code!(ACCEPTED, PARTIAL_SOLUTION, BUILD_ERROR);
+    declare_code!(ACCEPTED, PARTIAL_SOLUTION, REJECTED, BUILD_ERROR);
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Ord, PartialOrd, PartialEq, Eq, Hash)]
@@ -78,6 +78,16 @@ pub struct Status {
     pub code: String,
 }
 
+impl Default for Status {
+    /// Status which was not set yet
+    fn default() -> Status {
+        Status {
+            kind: StatusKind::NotSet,
+            code: String::new(),
+        }
+    }
+}
+
 bitflags! {
     #[derive(Serialize, Deserialize)]
     pub struct TestVisibleComponents: u32 {
@@ -102,11 +112,49 @@ bitflags! {
     }
 }
 
+/// Resources consumed by solution
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct ResourceUsage {
+    /// CPU time in milliseconds
+    pub time: Option<u64>,
+    /// Wall-clock time in milliseconds
+    pub wall_time: Option<u64>,
+    /// Peak memory usage in bytes
+    pub memory: Option<u64>,
+    /// Exit code of solution process
+    pub exit_code: Option<i64>,
+}
+
+impl ResourceUsage {
+    /// Returns component-wise maximum of `usages`, or None if `usages` is empty.
+    /// Exit code is not aggregated.
+    pub fn max_of(usages: impl IntoIterator<Item = ResourceUsage>) -> Option<ResourceUsage> {
+        fn max(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (a, b) => a.or(b),
+            }
+        }
+        usages.into_iter().fold(None, |acc, usage| {
+            let acc = acc.unwrap_or_default();
+            Some(ResourceUsage {
+                time: max(acc.time, usage.time),
+                wall_time: max(acc.wall_time, usage.wall_time),
+                memory: max(acc.memory, usage.memory),
+                exit_code: None,
+            })
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct JudgeLogTestRow {
     pub test_id: pom::TestId,
     pub status: Status,
     pub components: TestVisibleComponents,
+    /// Set if invoker reported it and `components` contain `RESOURCE_USAGE`
+    #[serde(default)]
+    pub resource_usage: Option<ResourceUsage>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
@@ -118,11 +166,42 @@ impl SubtaskId {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum SubtaskState {
+    Passed,
+    Failed,
+    /// Some dependency was not passed
+    Skipped,
+    /// Subtask was disabled in config
+    NotRun,
+}
+
+impl Default for SubtaskState {
+    /// State of subtask from judge log which does not report it
+    fn default() -> SubtaskState {
+        SubtaskState::NotRun
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct JudgeLogSubtaskRow {
     pub subtask_id: SubtaskId,
+    /// Group name from valuer config
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub state: SubtaskState,
+    /// For skipped subtask, dependency which was not passed
+    #[serde(default)]
+    pub skipped_because: Option<SubtaskId>,
+    /// Failed test with the smallest id, if it is included into judge log
+    #[serde(default)]
+    pub failed_test: Option<TestId>,
     pub score: u32,
     pub components: SubtaskVisibleComponents,
+    /// Maximum of resource usage over tests of this subtask
+    #[serde(default)]
+    pub max_resource_usage: Option<ResourceUsage>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
@@ -157,6 +236,19 @@ pub struct JudgeLog {
     pub subtasks: Vec<JudgeLogSubtaskRow>,
     pub score: u32,
     pub is_full: bool,
+    /// Aggregated verdict: `ACCEPTED` for full solution, `PARTIAL_SOLUTION`
+    /// if some score was gained, otherwise status of `failed_test`. If there
+    /// is no such test, it is `REJECTED`, or not set if some groups or tests
+    /// are excluded from judge log. `is_full` is also unset in the latter case.
+    #[serde(default)]
+    pub status: Status,
+    /// Failed test with the smallest id, if its status is included into
+    /// judge log
+    #[serde(default)]
+    pub failed_test: Option<TestId>,
+    /// Maximum of resource usage over all tests
+    #[serde(default)]
+    pub max_resource_usage: Option<ResourceUsage>,
 }
 
 impl Default for JudgeLog {
@@ -167,20 +259,59 @@ impl Default for JudgeLog {
             subtasks: Vec::new(),
             score: 0,
             is_full: false,
+            status: Status::default(),
+            failed_test: None,
+            max_resource_usage: None,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProblemInfo {
+    /// Group tag of each test
     pub tests: Vec<String>,
+    /// Additional tags of each test. May be shorter than `tests`.
+    #[serde(default)]
+    pub test_tags: Vec<Vec<String>>,
     // TODO: valuer config
 }
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+impl ProblemInfo {
+    /// Checks if test with index `test_idx` (0-based) is tagged with `tag`
+    pub fn test_has_tag(&self, test_idx: usize, tag: &str) -> bool {
+        self.tests[test_idx] == tag
+            || matches!(self.test_tags.get(test_idx), Some(tags) if tags.iter().any(|t| t == tag))
+    }
+}
+
+/// Part of test score gained by solution.
+/// Checker reports it when answer is partially correct.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TestFraction {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl TestFraction {
+    /// Returns fraction as a number in [0; 1]
+    pub fn share(self) -> f64 {
+        if self.denominator == 0 {
+            return 0.0;
+        }
+        (self.numerator as f64 / self.denominator as f64).min(1.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct TestDoneNotification {
     pub test_id: TestId,
     pub test_status: Status,
+    /// Set if checker reported partial credit
+    #[serde(default)]
+    pub fraction: Option<TestFraction>,
+    /// Set if invoker measured it
+    #[serde(default)]
+    pub resource_usage: Option<ResourceUsage>,
 }
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
@@ -196,4 +327,15 @@ pub enum ValuerResponse {
     LiveScore {
         score: u32,
     },
+    /// Result of the test is not needed anymore, so it can be stopped.
+    /// Notification about this test may still be sent, and it will be ignored.
+    CancelTest {
+        test_id: TestId,
+    },
+    /// Opaque valuer state. Invoker can store the latest snapshot
+    /// and pass it back to restarted valuer to resume valuing.
+    /// Only sent if valuer was asked to (svaluer does it with `--snapshots`).
+    Snapshot {
+        data: String,
+    },
 }